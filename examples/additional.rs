@@ -17,10 +17,11 @@ struct Additional {
 }
 
 fn main() {
-    let mut settings = Settings::default();
-    settings.enable_env = Some("Additional env description!".into());
-    settings.enable_stdin = Some("Additional stdin description!".into());
-    settings.enable_working_dir = Some("Additional working dir description!".into());
+    let settings = Settings::builder()
+        .env("Additional env description!")
+        .stdin("Additional stdin description!")
+        .working_dir("Additional working dir description!")
+        .build();
 
     klask::run_derived::<Additional, _>(settings, |additional| {
         if !additional.hide_environment_variables {
@@ -0,0 +1,27 @@
+//! Builds settings by chaining methods
+use clap::Parser;
+use klask::{Settings, Theme};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct Builder {
+    /// File to print
+    #[arg(long)]
+    file: Option<PathBuf>,
+}
+
+fn main() {
+    let settings = Settings::builder()
+        .env("Variables are passed to the program")
+        .stdin("Printed after the file")
+        .working_dir("")
+        .theme(Theme::Light)
+        .build();
+
+    klask::run_derived::<Builder, _>(settings, |builder| {
+        if let Some(file) = builder.file {
+            println!("{}", std::fs::read_to_string(file).unwrap());
+        }
+        std::io::copy(&mut std::io::stdin(), &mut std::io::stdout()).unwrap();
+    });
+}
@@ -0,0 +1,46 @@
+//! Adds a tab with documentation next to the arguments
+use clap::Parser;
+use eframe::egui::Ui;
+use klask::{ExtraTab, Settings, TabContent};
+
+#[derive(Parser)]
+struct CustomTab {
+    /// Text to transform
+    #[arg(long)]
+    text: String,
+    /// Print it in upper case
+    #[arg(long)]
+    upper: bool,
+}
+
+struct Docs {
+    show_examples: bool,
+}
+
+impl TabContent for Docs {
+    fn label(&self) -> &str {
+        "Documentation"
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.heading("Usage");
+        ui.label("Type some text and press run. It's printed back, in upper case if checked.");
+        ui.checkbox(&mut self.show_examples, "Show examples");
+        if self.show_examples {
+            ui.monospace("--text hello --upper  =>  HELLO");
+            ui.monospace("--text Hello          =>  Hello");
+        }
+    }
+}
+
+fn main() {
+    let mut settings = Settings::default();
+    settings.extra_tabs.push(ExtraTab::new(Docs {
+        show_examples: false,
+    }));
+
+    klask::run_derived::<CustomTab, _>(settings, |args| match args.upper {
+        true => println!("{}", args.text.to_uppercase()),
+        false => println!("{}", args.text),
+    });
+}
@@ -1,5 +1,5 @@
 use clap::{Parser, ValueHint};
-use klask::{Localization, Settings};
+use klask::{DefaultTab, Localization, Settings};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
@@ -21,6 +21,8 @@ fn main() {
     settings.enable_env = Some("Additional env description!".into());
     settings.enable_stdin = Some("Additional stdin description!".into());
     settings.enable_working_dir = Some("Additional working dir description!".into());
+    // Open on the translated environment variables tab
+    settings.default_tab = DefaultTab::Env;
     settings.localization = polish_localization_exaple();
 
     klask::run_derived::<LocalizationExample, _>(settings, |_| {})
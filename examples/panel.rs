@@ -0,0 +1,59 @@
+//! Shows the form of klask next to other widgets of an egui app
+use clap::{CommandFactory, FromArgMatches, Parser};
+use eframe::egui;
+use klask::{KlaskPanel, Settings};
+
+#[derive(Parser)]
+struct Panel {
+    /// Who to greet
+    #[arg(long)]
+    name: String,
+    /// How many times
+    #[arg(long, default_value_t = 1)]
+    count: u32,
+}
+
+struct App {
+    panel: KlaskPanel,
+    clicks: u32,
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::SidePanel::left("klask")
+            .min_width(300.0)
+            .show(ctx, |ui| {
+                ui.add(&mut self.panel);
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("The rest of the app");
+            if ui
+                .button(format!("Clicked {} times", self.clicks))
+                .clicked()
+            {
+                self.clicks += 1;
+            }
+        });
+    }
+}
+
+fn main() {
+    let panel = KlaskPanel::new(Panel::command(), Settings::default(), |matches| {
+        let panel = Panel::from_arg_matches(matches).map_err(|err| err.to_string())?;
+        if panel.name.is_empty() {
+            return Err("Name can't be empty".to_string());
+        }
+        for _ in 0..panel.count {
+            println!("Hello {}", panel.name);
+        }
+        Ok(())
+    });
+
+    eframe::run_native(
+        "Panel",
+        eframe::NativeOptions::default(),
+        Box::new(|_| Box::new(App { panel, clicks: 0 })),
+    )
+    .unwrap();
+}
@@ -1,108 +1,928 @@
-use crate::{arg_state::ArgState, settings::Localization};
-use clap::Command;
-use eframe::egui::{widgets::Widget, Grid, Response, Ui};
-use std::collections::BTreeMap;
+use crate::{
+    arg_state::{json_string, ArgKind, ArgSettings, ArgState},
+    field_history::FieldHistory,
+    settings::{ArgSort, Localization, SubcommandDisplay, Validator},
+    to_sentence_case, ExecutionError,
+};
+use clap::{Arg, ArgAction, ArgGroup, Command};
+use eframe::egui::{widgets::Widget, Align, ComboBox, Grid, Key, Layout, Response, ScrollArea, Ui};
+use std::{
+    cell::OnceCell,
+    collections::{BTreeMap, HashMap},
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
 use uuid::Uuid;
 
+/// With more subcommands than this they are picked from a searchable dropdown
+/// instead of columns.
+const MAX_SUBCOMMAND_COLUMNS: usize = 5;
+/// Width of the subcommand list with [`SubcommandDisplay::SidePanel`]
+const SUBCOMMAND_PANEL_WIDTH: f32 = 150.0;
+/// Longer subcommand lists scroll
+const SUBCOMMAND_PANEL_MAX_HEIGHT: f32 = 400.0;
+
 #[derive(Debug, Clone)]
-pub struct AppState<'s> {
+pub struct AppState {
     id: Uuid,
     about: Option<String>,
-    args: Vec<ArgState<'s>>,
-    subcommands: BTreeMap<String, AppState<'s>>,
+    args: Vec<LazyArgState>,
+    /// Indices into `args` in the order they are shown, from [`ArgSettings::arg_order`]
+    /// and [`ArgSettings::arg_sort`].
+    /// `args` itself stays in definition order, so positional args are passed in order.
+    order: Vec<usize>,
+    groups: Vec<ExclusiveGroup>,
+    /// Ids of args that conflict with each arg, in both directions
+    conflicts: HashMap<String, Vec<String>>,
+    /// From [`ArgSettings::conditional_visibility`]
+    conditional_visibility: Arc<[(String, String, Vec<String>)]>,
+    /// Whether each arg with a visibility condition is shown, updated every frame by
+    /// [`AppState::update_visibility`]. Other args are always shown.
+    visible: HashMap<String, bool>,
+    subcommands: BTreeMap<String, LazySubcommand>,
     current: Option<String>,
+    /// From [`Command::is_subcommand_required_set`]
+    subcommand_required: bool,
+    /// Some when the command allows external subcommands
+    external: Option<ExternalSubcommand>,
+    /// Text typed into the subcommand dropdown, cleared when it closes
+    subcommand_search: String,
+    subcommand_display: SubcommandDisplay,
+    /// Args are shown in rows instead of a grid, see [`ArgSettings::compact_mode`]
+    compact_mode: bool,
+    localization: Arc<Localization>,
+}
+
+/// Arg that is turned into an [`ArgState`] the first time it's used, like when
+/// it's shown or its value is read. Commands with many subcommands only create
+/// the args of the subcommands that are picked.
+#[derive(Debug, Clone)]
+struct LazyArgState {
+    arg: Arg,
+    localization: Arc<Localization>,
+    settings: ArgSettings,
+    state: OnceCell<ArgState>,
+}
+
+impl LazyArgState {
+    fn new(arg: &Arg, localization: &Arc<Localization>, settings: ArgSettings) -> Self {
+        Self {
+            arg: arg.clone(),
+            localization: Arc::clone(localization),
+            settings,
+            state: OnceCell::new(),
+        }
+    }
+
+    fn create(&self) -> ArgState {
+        ArgState::new(&self.arg, &self.localization, self.settings.clone())
+    }
+
+    /// Same as `ArgState::id`, without creating the state
+    fn id(&self) -> &str {
+        self.arg.get_id().as_str()
+    }
+
+    /// Like [`ArgState::optional`], without creating the state
+    fn is_required(&self) -> bool {
+        let env_set = self
+            .arg
+            .get_env()
+            .is_some_and(|env| std::env::var(env).is_ok());
+        self.arg.is_required_set() && !env_set
+    }
 }
 
-impl<'s> AppState<'s> {
-    pub fn new(app: &Command, localization: &'s Localization) -> Self {
+impl Deref for LazyArgState {
+    type Target = ArgState;
+
+    fn deref(&self) -> &Self::Target {
+        self.state.get_or_init(|| self.create())
+    }
+}
+
+impl DerefMut for LazyArgState {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        if self.state.get().is_none() {
+            let state = self.create();
+            self.state = OnceCell::from(state);
+        }
+        self.state.get_mut().unwrap()
+    }
+}
+
+/// Subcommand that is turned into an [`AppState`] the first time it's used, like when
+/// it's selected. Commands with many nested subcommands like `cargo` only create the
+/// forms of the subcommands that are picked.
+#[derive(Debug, Clone)]
+struct LazySubcommand {
+    app: Command,
+    localization: Arc<Localization>,
+    settings: ArgSettings,
+    state: OnceCell<AppState>,
+}
+
+impl LazySubcommand {
+    fn new(app: &Command, localization: &Arc<Localization>, settings: ArgSettings) -> Self {
+        Self {
+            app: app.clone(),
+            localization: Arc::clone(localization),
+            settings,
+            state: OnceCell::new(),
+        }
+    }
+
+    fn create(&self) -> AppState {
+        AppState::new(&self.app, &self.localization, self.settings.clone())
+    }
+}
+
+impl Deref for LazySubcommand {
+    type Target = AppState;
+
+    fn deref(&self) -> &Self::Target {
+        self.state.get_or_init(|| self.create())
+    }
+}
+
+impl DerefMut for LazySubcommand {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        if self.state.get().is_none() {
+            let state = self.create();
+            self.state = OnceCell::from(state);
+        }
+        self.state.get_mut().unwrap()
+    }
+}
+
+/// Subcommand that isn't known to clap, from [`Command::allow_external_subcommands`].
+/// Its name and args are typed in like positional args.
+#[derive(Debug, Clone)]
+struct ExternalSubcommand {
+    /// Picked instead of a known subcommand, `AppState::current` is None then
+    selected: bool,
+    name: ArgState,
+    args: ArgState,
+}
+
+/// Group of args where only one can be set, displayed as a radio selection
+#[derive(Debug, Clone)]
+struct ExclusiveGroup {
+    name: String,
+    /// Indices into `AppState::args`
+    members: Vec<usize>,
+    selected: Option<usize>,
+    required: bool,
+}
+
+impl AppState {
+    pub fn new(app: &Command, localization: &Arc<Localization>, settings: ArgSettings) -> Self {
+        let settings = ArgSettings {
+            inline_long_help: settings.inline_long_help || app.is_next_line_help_set(),
+            ..settings
+        };
+        // Args are kept in declaration order. Ideally they would be sorted by
+        // `display_order` to match `--help`, but clap 4.1 keeps
+        // `Arg::get_display_order` crate-private.
+        // Help and version flags print instead of running, also when they have other ids
         let args = app
             .get_arguments()
             .filter(|a| a.get_id() != "help" && a.get_id() != "version")
-            .map(|a| ArgState::new(a, localization))
+            .filter(|a| !matches!(a.get_action(), ArgAction::Help | ArgAction::Version))
+            .map(|a| LazyArgState::new(a, localization, settings.clone()))
+            .collect::<Vec<_>>();
+
+        let mut order: Vec<usize> = (0..args.len()).collect();
+        match settings.arg_sort {
+            ArgSort::Definition => {}
+            ArgSort::Alphabetical => {
+                order.sort_by_cached_key(|&i| to_sentence_case(args[i].id()).to_lowercase())
+            }
+            ArgSort::RequiredFirst => order.sort_by_key(|&i| !args[i].is_required()),
+            ArgSort::OptionalFirst => order.sort_by_key(|&i| args[i].is_required()),
+        }
+        // Stable, so the rest keeps the order from above
+        order.sort_by_key(|&i| {
+            let position = settings.arg_order.iter().position(|id| id == args[i].id());
+            position.unwrap_or(usize::MAX)
+        });
+
+        let groups = app
+            .get_groups()
+            .filter(|g| !ArgGroup::clone(g).is_multiple())
+            .map(|g| ExclusiveGroup {
+                name: to_sentence_case(g.get_id().as_str()),
+                members: g
+                    .get_args()
+                    .filter_map(|id| args.iter().position(|a| a.id() == id.as_str()))
+                    .collect(),
+                selected: None,
+                required: g.is_required_set(),
+            })
+            .filter(|g| g.members.len() > 1)
             .collect();
 
+        let mut conflicts: HashMap<String, Vec<String>> = HashMap::new();
+        for arg in app.get_arguments() {
+            for other in app.get_arg_conflicts_with(arg) {
+                let (id, other) = (arg.get_id().to_string(), other.get_id().to_string());
+                conflicts.entry(id.clone()).or_default().push(other.clone());
+                conflicts.entry(other).or_default().push(id);
+            }
+        }
+
         let subcommands = app
             .get_subcommands()
-            .map(|app| (app.get_name().to_string(), AppState::new(app, localization)))
+            .map(|app| {
+                (
+                    app.get_name().to_string(),
+                    LazySubcommand::new(app, localization, settings.clone()),
+                )
+            })
             .collect();
 
-        AppState {
+        let external = app.is_allow_external_subcommands_set().then(|| {
+            let name = Arg::new("subcommand")
+                .action(ArgAction::Set)
+                .required(app.is_subcommand_required_set());
+            let args = Arg::new("args").action(ArgAction::Append);
+            ExternalSubcommand {
+                selected: app.get_subcommands().next().is_none(),
+                name: ArgState::new(&name, localization, settings.clone()),
+                args: ArgState::new(&args, localization, settings.clone()),
+            }
+        });
+
+        let mut state = AppState {
             id: Uuid::new_v4(),
             about: app.get_about().map(|v| v.to_string()),
             args,
+            order,
+            groups,
+            conflicts,
+            conditional_visibility: Arc::clone(&settings.conditional_visibility),
+            visible: HashMap::new(),
             subcommands,
             current: app
                 .get_subcommands()
                 .map(|app| app.get_name().to_string())
                 .next(),
+            subcommand_required: app.is_subcommand_required_set(),
+            external,
+            subcommand_search: String::new(),
+            subcommand_display: settings.subcommand_display,
+            compact_mode: settings.compact_mode,
+            localization: Arc::clone(localization),
+        };
+
+        for group in 0..state.groups.len() {
+            let first = state.groups[group].members[0];
+            let required = state.groups[group].required;
+            state.select_in_group(group, required.then_some(first));
         }
+
+        state
+    }
+
+    /// Selects which member of an exclusive group can be edited. The value of
+    /// the previously selected member is cleared.
+    fn select_in_group(&mut self, group: usize, selected: Option<usize>) {
+        let group = &mut self.groups[group];
+
+        if let Some(previous) = std::mem::replace(&mut group.selected, selected) {
+            if Some(previous) != selected {
+                self.args[previous].reset();
+            }
+        }
+
+        for &member in &group.members {
+            self.args[member].enabled = group.selected == Some(member);
+        }
+    }
+
+    /// A set arg that conflicts with the arg at `index`. Args that are set themselves
+    /// aren't disabled, clap reports the conflict when running.
+    fn conflicting_arg(&self, index: usize) -> Option<&ArgState> {
+        let arg = &self.args[index];
+        let conflicts = self.conflicts.get(arg.id()).filter(|_| !arg.is_changed())?;
+        self.args
+            .iter()
+            .find(|other| conflicts.iter().any(|id| id == other.id()) && other.is_changed())
+            .map(Deref::deref)
     }
 
-    pub fn update_validation_error(&mut self, name: &str, message: &str) {
+    /// Decides which args with a condition in [`ArgSettings::conditional_visibility`] are
+    /// shown, from the values of the trigger args in this command. Args that get hidden
+    /// are cleared.
+    pub fn update_visibility(&mut self) {
+        let mut visible: HashMap<String, bool> = HashMap::new();
+        for (trigger, value, ids) in self.conditional_visibility.iter() {
+            let triggered = self
+                .args
+                .iter()
+                .find(|arg| arg.id() == trigger)
+                .is_some_and(|arg| arg.has_value(value));
+            for id in ids {
+                *visible.entry(id.clone()).or_default() |= triggered;
+            }
+        }
+
+        // Args that were never created still have their default
+        for arg in self.args.iter_mut().filter_map(|arg| arg.state.get_mut()) {
+            if visible.get(&arg.id) == Some(&false) && arg.is_changed() {
+                arg.reset();
+            }
+        }
+        self.visible = visible;
+    }
+
+    /// Whether the arg `id` is shown, see [`AppState::update_visibility`]
+    fn is_visible(&self, id: &str) -> bool {
+        self.visible.get(id).copied().unwrap_or(true)
+    }
+
+    /// Switches this form and all subcommands to rows or back to a grid,
+    /// see [`ArgSettings::compact_mode`]
+    pub fn set_compact_mode(&mut self, compact_mode: bool) {
+        self.compact_mode = compact_mode;
+        for subcommand in self.subcommands.values_mut() {
+            // Subcommands that were never created start in the new mode
+            subcommand.settings.compact_mode = compact_mode;
+            if let Some(state) = subcommand.state.get_mut() {
+                state.set_compact_mode(compact_mode);
+            }
+        }
+    }
+
+    /// Shows the error on the arg called `name` in the subcommand at `path`, relative to
+    /// this command, and clears it everywhere else. An empty name clears all errors.
+    pub fn update_validation_error(&mut self, path: &[String], name: &str, message: &str) {
+        let (here, below) = match path.split_first() {
+            Some((subcommand, rest)) => ("", Some((subcommand, rest))),
+            None => (name, None),
+        };
         for arg in &mut self.args {
-            arg.update_validation_error(name, message);
+            arg.update_validation_error(here, message);
         }
 
         if let Some(current) = &self.current {
-            self.subcommands
-                .get_mut(current)
-                .unwrap()
-                .update_validation_error(name, message);
+            let subcommand = self.subcommands.get_mut(current).unwrap();
+            match below {
+                Some((name_below, rest)) if name_below == current => {
+                    subcommand.update_validation_error(rest, name, message)
+                }
+                _ => subcommand.update_validation_error(&[], "", ""),
+            }
+        } else if let Some(external) = self.selected_external_mut() {
+            external.name.update_validation_error(here, message);
+            external.args.update_validation_error(here, message);
         }
     }
 
+    fn selected_external(&self) -> Option<&ExternalSubcommand> {
+        self.external.as_ref().filter(|e| e.selected)
+    }
+
+    fn selected_external_mut(&mut self) -> Option<&mut ExternalSubcommand> {
+        self.external.as_mut().filter(|e| e.selected)
+    }
+
     pub fn get_cmd_args(&self, mut args: Vec<String>) -> Result<Vec<String>, String> {
-        for arg in &self.args {
-            args = arg.get_cmd_args(args)?;
+        for (index, arg) in self.args.iter().enumerate() {
+            if arg.enabled && self.conflicting_arg(index).is_none() && self.is_visible(arg.id()) {
+                args = arg.get_cmd_args(args)?;
+            }
         }
 
         if let Some(current) = &self.current {
             args.push(current.clone());
             self.subcommands[current].get_cmd_args(args)
+        } else if let Some(external) = self.selected_external() {
+            // Errors if the name is required, args without a name would be taken as one
+            args = external.name.get_cmd_args(args)?;
+            match external.name.is_changed() {
+                true => external.args.get_cmd_args(args),
+                false => Ok(args),
+            }
         } else {
             Ok(args)
         }
     }
+
+    /// Args for running once per value of the multiple value arg `id`, each paired with
+    /// its value. Empty when the arg isn't in the form (or the selected subcommands)
+    /// or has no values.
+    pub fn get_batch_cmd_args(&self, id: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+        let mut state = self.clone();
+        let items: Vec<String> = match state.find_arg_mut(id) {
+            Some(ArgState {
+                kind: ArgKind::MultipleStrings { values, .. },
+                ..
+            }) => values.iter().map(|(v, _)| v.clone()).collect(),
+            _ => return Ok(vec![]),
+        };
+
+        items
+            .into_iter()
+            .map(|item| {
+                if let Some(ArgState {
+                    kind: ArgKind::MultipleStrings { values, .. },
+                    ..
+                }) = state.find_arg_mut(id)
+                {
+                    *values = vec![(item.clone(), Uuid::new_v4())];
+                }
+                Ok((item, state.get_cmd_args(vec![])?))
+            })
+            .collect()
+    }
+
+    /// Fills the enabled args, including in the selected subcommands, with random values.
+    #[cfg(feature = "randomize")]
+    pub fn randomize(&mut self, rng: &mut impl rand::Rng) {
+        for arg in &mut self.args {
+            if arg.enabled {
+                arg.randomize_value(rng);
+            }
+        }
+
+        if let Some(current) = &self.current {
+            self.subcommands.get_mut(current).unwrap().randomize(rng);
+        }
+    }
+
+    /// Shows `message` as the validation error of the arg `id` (in the selected
+    /// subcommands), from [`crate::output::FIELD_ERROR_PREFIX`]. Returns false if there's
+    /// no such arg.
+    pub fn set_field_error(&mut self, id: &str, message: &str) -> bool {
+        match self.find_arg_mut(id) {
+            Some(arg) => {
+                arg.validation_error = Some(message.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finds an arg in this form or the selected subcommands
+    fn find_arg_mut(&mut self, id: &str) -> Option<&mut ArgState> {
+        if let Some(index) = self.args.iter().position(|a| a.id() == id) {
+            return Some(&mut self.args[index]);
+        }
+
+        let current = self.current.as_ref()?;
+        self.subcommands.get_mut(current)?.find_arg_mut(id)
+    }
+
+    /// Runs [`crate::Settings::validators`] on the values of args (in the selected
+    /// subcommands) that will be passed to the program.
+    pub fn validate(&self, validators: &HashMap<String, Validator>) -> Result<(), ExecutionError> {
+        for (index, arg) in self.args.iter().enumerate() {
+            let Some(validator) = validators.get(&arg.id) else {
+                continue;
+            };
+            if !arg.enabled || self.conflicting_arg(index).is_some() {
+                continue;
+            }
+
+            let values: Vec<&String> = match &arg.kind {
+                ArgKind::String { value, .. } => vec![&value.0],
+                ArgKind::MultipleStrings { values, .. } => values.iter().map(|(v, _)| v).collect(),
+                ArgKind::Occurences(_) | ArgKind::Bool(_) | ArgKind::OptionalBool(_) => vec![],
+            };
+
+            for value in values.into_iter().filter(|v| !v.is_empty()) {
+                validator
+                    .call(value)
+                    .map_err(|message| ExecutionError::ValidationError {
+                        path: vec![],
+                        name: arg.name.clone(),
+                        message,
+                    })?;
+            }
+        }
+
+        let Some(current) = &self.current else {
+            return Ok(());
+        };
+        self.subcommands[current]
+            .validate(validators)
+            .map_err(|err| match err {
+                ExecutionError::ValidationError {
+                    mut path,
+                    name,
+                    message,
+                } => {
+                    path.insert(0, current.clone());
+                    ExecutionError::ValidationError {
+                        path,
+                        name,
+                        message,
+                    }
+                }
+                err => err,
+            })
+    }
+
+    /// The form as a JSON object. `args` maps arg ids to their values and `subcommand`
+    /// is null or an object with the `name` of the selected subcommand, its `args`
+    /// and its `subcommand`. Disabled args are left out. External subcommands have
+    /// their args as a list instead.
+    pub fn to_json(&self) -> String {
+        let args: Vec<String> = (0..self.args.len())
+            .filter(|&i| self.args[i].enabled && self.conflicting_arg(i).is_none())
+            .map(|i| {
+                format!(
+                    "{}: {}",
+                    json_string(&self.args[i].id),
+                    self.args[i].to_json()
+                )
+            })
+            .collect();
+
+        let subcommand = match &self.current {
+            Some(current) => {
+                let json = self.subcommands[current].to_json();
+                format!("{{\"name\": {}, {}", json_string(current), &json[1..])
+            }
+            None => match self.selected_external() {
+                Some(external) if external.name.is_changed() => format!(
+                    "{{\"name\": {}, \"args\": {}, \"subcommand\": null}}",
+                    external.name.to_json(),
+                    external.args.to_json()
+                ),
+                _ => "null".into(),
+            },
+        };
+
+        format!(
+            "{{\"args\": {{{}}}, \"subcommand\": {subcommand}}}",
+            args.join(", ")
+        )
+    }
+
+    /// Adds the values of the enabled args (in the selected subcommands) to their history,
+    /// see [`ArgState::record_history`].
+    pub fn record_history(&mut self) {
+        // Args that were never created still have their default
+        for arg in self.args.iter_mut().filter_map(|arg| arg.state.get_mut()) {
+            if arg.enabled && arg.conflicts_with_set.is_none() {
+                arg.record_history();
+            }
+        }
+
+        if let Some(current) = &self.current {
+            if let Some(subcommand) = self.subcommands.get_mut(current) {
+                subcommand.record_history();
+            }
+        }
+    }
+
+    /// Replaces the entries in `history` with the ones of every created arg,
+    /// including in subcommands that aren't selected.
+    pub fn collect_history(&self, history: &mut FieldHistory) {
+        for arg in self.args.iter().filter_map(|arg| arg.state.get()) {
+            match arg.history.is_empty() {
+                true => history.fields.remove(&arg.id),
+                false => history.fields.insert(arg.id.clone(), arg.history.clone()),
+            };
+        }
+
+        for subcommand in self.subcommands.values().filter_map(|s| s.state.get()) {
+            subcommand.collect_history(history);
+        }
+    }
+
+    /// Arg (in the selected subcommands) whose text field was focused or row clicked last,
+    /// see [`ArgState::focused_at`]
+    pub fn focused_arg(&self) -> Option<&ArgState> {
+        let args = self.args.iter().filter_map(|arg| arg.state.get());
+        let subcommand = self
+            .current
+            .as_ref()
+            .and_then(|current| self.subcommands.get(current))
+            .and_then(|subcommand| subcommand.focused_arg());
+        args.chain(subcommand)
+            .filter_map(|arg| Some((arg.focused_at?, arg)))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, arg)| arg)
+    }
+
+    /// Marks the first required arg without a value (in display order, then in the selected
+    /// subcommands) to be scrolled to and focused. Returns false if none is missing.
+    pub fn focus_first_missing(&mut self) -> bool {
+        match self.first_missing() {
+            Some(arg) => {
+                arg.scroll_to = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether this command or a selected subcommand requires a subcommand but has none
+    /// selected. A selected external subcommand counts, its name is a required arg then.
+    pub fn subcommand_missing(&self) -> bool {
+        match &self.current {
+            Some(current) => self.subcommands[current].subcommand_missing(),
+            None => self.subcommand_required && self.selected_external().is_none(),
+        }
+    }
+
+    /// Whether [`AppState::focus_first_missing`] would find an arg
+    pub fn has_missing(&mut self) -> bool {
+        self.first_missing().is_some()
+    }
+
+    fn first_missing(&mut self) -> Option<&mut ArgState> {
+        let args = &self.args;
+        // Optional lazy args aren't created just for checking
+        let found = self.order.iter().copied().find(|&i| {
+            args[i].is_required() && self.is_visible(args[i].id()) && args[i].is_missing()
+        });
+        match found {
+            Some(i) => Some(&mut self.args[i]),
+            None => self
+                .current
+                .as_ref()
+                .and_then(|current| self.subcommands.get_mut(current))
+                .and_then(|subcommand| subcommand.first_missing()),
+        }
+    }
+
+    /// Names and values of the args (in the selected subcommands) that were changed
+    /// from their defaults and will be passed to the program.
+    pub fn get_changed_values(&self, mut values: Vec<(String, String)>) -> Vec<(String, String)> {
+        values.extend(
+            (0..self.args.len())
+                .filter(|&i| self.args[i].enabled && self.conflicting_arg(i).is_none())
+                .filter(|&i| self.args[i].is_changed())
+                .map(|i| (self.args[i].name.clone(), self.args[i].get_display_value())),
+        );
+
+        if let Some(current) = &self.current {
+            self.subcommands[current].get_changed_values(values)
+        } else {
+            if let Some(external) = self.selected_external() {
+                for arg in [&external.name, &external.args] {
+                    if arg.is_changed() {
+                        values.push((arg.name.clone(), arg.get_display_value()));
+                    }
+                }
+            }
+            values
+        }
+    }
+
+    /// Ids of the args (in the selected subcommands) that were changed from their defaults.
+    pub fn get_changed_args(&self, mut ids: Vec<String>) -> Vec<String> {
+        ids.extend(
+            self.args
+                .iter()
+                .filter(|arg| arg.is_changed())
+                .map(|arg| arg.id.clone()),
+        );
+
+        if let Some(current) = &self.current {
+            self.subcommands[current].get_changed_args(ids)
+        } else {
+            ids
+        }
+    }
 }
 
-impl Widget for &mut AppState<'_> {
+impl AppState {
+    /// Rows of the args and groups, in a grid with the names in the first column
+    /// or each in a horizontal row without one
+    fn ui_args(&mut self, ui: &mut Ui, grid: bool) {
+        let row = |ui: &mut Ui, add: &mut dyn FnMut(&mut Ui)| match grid {
+            true => {
+                add(ui);
+                ui.end_row();
+            }
+            false => {
+                ui.horizontal(add);
+            }
+        };
+
+        // Groups are shown before whichever member comes first
+        let mut groups_shown = vec![false; self.groups.len()];
+        for position in 0..self.order.len() {
+            let index = self.order[position];
+            if let Some(group) = self.groups.iter().position(|g| g.members.contains(&index)) {
+                if !std::mem::replace(&mut groups_shown[group], true) {
+                    row(ui, &mut |ui| self.ui_group(ui, group));
+                }
+            }
+
+            // Hidden rows are left out, an invisible ui would still take up space
+            if !self.is_visible(self.args[index].id()) {
+                continue;
+            }
+
+            let conflict = self
+                .conflicting_arg(index)
+                .map(|other| other.call_name.as_ref().unwrap_or(&other.name).clone());
+            self.args[index].conflicts_with_set = conflict;
+            row(ui, &mut |ui| {
+                ui.add(&mut *self.args[index]);
+            });
+        }
+    }
+
+    fn ui_group(&mut self, ui: &mut Ui, group: usize) {
+        let ExclusiveGroup {
+            name,
+            members,
+            selected,
+            required,
+        } = &self.groups[group];
+
+        ui.label(name);
+
+        let selected = *selected;
+        let mut new_selected = selected;
+        ui.horizontal_wrapped(|ui| {
+            if !required {
+                ui.radio_value(&mut new_selected, None, "None");
+            }
+            for &member in members {
+                ui.radio_value(&mut new_selected, Some(member), &self.args[member].name);
+            }
+        });
+
+        if new_selected != selected {
+            self.select_in_group(group, new_selected);
+        }
+    }
+
+    /// Dropdown with a search field, names starting with the search are listed first.
+    /// Enter picks the first match and Escape closes it without changing the selection.
+    fn ui_subcommand_search(&mut self, ui: &mut Ui) {
+        let Self {
+            id,
+            subcommands,
+            current,
+            external,
+            subcommand_search: search,
+            localization,
+            ..
+        } = self;
+
+        let selected_text = match (&current, &external) {
+            (Some(current), _) => current.as_str(),
+            (None, Some(external)) if external.selected => &localization.other_subcommand,
+            (None, _) => "",
+        };
+        let open = ComboBox::from_id_source((*id, "subcommand"))
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                ui.text_edit_singleline(search).request_focus();
+
+                let query = search.to_lowercase();
+                let mut matching: Vec<&String> = subcommands
+                    .keys()
+                    .filter(|name| name.to_lowercase().contains(&query))
+                    .collect();
+                matching.sort_by_key(|name| !name.to_lowercase().starts_with(&query));
+
+                if ui.input(|i| i.key_pressed(Key::Enter)) {
+                    if let Some(&first) = matching.first() {
+                        *current = Some(first.clone());
+                        ui.memory_mut(|m| m.close_popup());
+                    }
+                }
+
+                for name in matching {
+                    ui.selectable_value(current, Some(name.clone()), name);
+                }
+
+                if let Some(external) = external {
+                    let other = &localization.other_subcommand;
+                    if ui.selectable_label(external.selected, other).clicked() {
+                        *current = None;
+                        external.selected = true;
+                    }
+                }
+            })
+            .inner;
+
+        if open.is_none() {
+            search.clear();
+        }
+    }
+}
+
+impl Widget for &mut AppState {
     fn ui(self, ui: &mut Ui) -> Response {
         ui.vertical(|ui| {
-            if let Some(ref about) = self.about {
+            if let Some(about) = self.about.as_ref().filter(|_| !self.compact_mode) {
                 ui.label(about);
             }
 
+            self.update_visibility();
+
             // Even empty grid adds an empty line
             if !self.args.is_empty() {
-                Grid::new(self.id)
-                    .num_columns(2)
-                    .striped(true)
-                    .show(ui, |ui| {
-                        for arg in &mut self.args {
-                            ui.add(arg);
-                            ui.end_row();
-                        }
-                    });
+                match self.compact_mode {
+                    true => self.ui_args(ui, false),
+                    false => {
+                        Grid::new(self.id)
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| self.ui_args(ui, true));
+                    }
+                }
             }
 
             ui.separator();
 
-            if !self.subcommands.is_empty() {
-                // It probably should be changed to wrapping when there are more than a few
-                ui.columns(self.subcommands.len(), |ui| {
+            if self.subcommand_display == SubcommandDisplay::SidePanel
+                && !self.subcommands.is_empty()
+            {
+                ui.horizontal_top(|ui| {
+                    self.ui_subcommand_panel(ui);
+                    ui.separator();
+                    ui.vertical(|ui| self.ui_current_subcommand(ui));
+                });
+                return;
+            }
+
+            if self.subcommands.len() > MAX_SUBCOMMAND_COLUMNS {
+                self.ui_subcommand_search(ui);
+            } else if !self.subcommands.is_empty() {
+                let columns = self.subcommands.len() + usize::from(self.external.is_some());
+                ui.columns(columns, |ui| {
                     for (i, name) in self.subcommands.keys().enumerate() {
                         ui[i].selectable_value(&mut self.current, Some(name.clone()), name);
                     }
+
+                    if let Some(external) = &mut self.external {
+                        let other = &self.localization.other_subcommand;
+                        if ui[columns - 1]
+                            .selectable_label(external.selected, other)
+                            .clicked()
+                        {
+                            self.current = None;
+                            external.selected = true;
+                        }
+                    }
                 });
             }
 
-            if let Some(current) = &self.current {
-                ui.add(self.subcommands.get_mut(current).unwrap());
-            }
+            self.ui_current_subcommand(ui);
         })
         .response
     }
 }
 
+impl AppState {
+    /// Form of the selected subcommand or the external one
+    fn ui_current_subcommand(&mut self, ui: &mut Ui) {
+        if let Some(external) = &mut self.external {
+            external.selected &= self.current.is_none();
+        }
+
+        if let Some(current) = &self.current {
+            ui.add(&mut **self.subcommands.get_mut(current).unwrap());
+        } else if let Some(external) = self.external.as_mut().filter(|e| e.selected) {
+            Grid::new((self.id, "external"))
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.add(&mut external.name);
+                    ui.end_row();
+                    ui.add(&mut external.args);
+                    ui.end_row();
+                });
+        }
+    }
+
+    /// Subcommands as a scrollable list of buttons, see [`SubcommandDisplay::SidePanel`]
+    fn ui_subcommand_panel(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| {
+            ui.set_width(SUBCOMMAND_PANEL_WIDTH);
+            ScrollArea::vertical()
+                .id_source((self.id, "subcommands"))
+                .max_height(SUBCOMMAND_PANEL_MAX_HEIGHT)
+                .show(ui, |ui| {
+                    ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+                        for name in self.subcommands.keys() {
+                            ui.selectable_value(&mut self.current, Some(name.clone()), name);
+                        }
+
+                        if let Some(external) = &mut self.external {
+                            let other = &self.localization.other_subcommand;
+                            if ui.selectable_label(external.selected, other).clicked() {
+                                self.current = None;
+                                external.selected = true;
+                            }
+                        }
+                    });
+                });
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,11 +1,14 @@
-use super::AppState;
+use super::{AppState, LazyArgState};
 use crate::{
-    arg_state::{ArgKind, ArgState},
-    settings::Localization,
+    arg_state::{ArgKind, ArgSettings},
+    field_history::FieldHistory,
+    settings::{ArgSort, Localization, Validator},
+    ExecutionError,
 };
 use clap::builder::NonEmptyStringValueParser;
-use clap::{CommandFactory, FromArgMatches, Parser, ValueHint};
-use std::{fmt::Debug, path::PathBuf};
+use clap::{ArgGroup, Command, CommandFactory, FromArgMatches, Parser, ValueHint};
+use eframe::egui::Color32;
+use std::{collections::HashMap, fmt::Debug, path::PathBuf, sync::Arc};
 use uuid::Uuid;
 
 #[derive(Debug, Parser, PartialEq, Eq)]
@@ -44,6 +47,48 @@ fn simple() {
     )
 }
 
+#[test]
+fn occurrences_short() {
+    #[derive(Debug, Parser)]
+    struct Verbose {
+        #[arg(short, long, action = clap::ArgAction::Count)]
+        verbose: u8,
+    }
+
+    let app = Verbose::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    app_state.args[0].occurrences(3);
+    assert_eq!(app_state.get_cmd_args(vec![]).unwrap(), ["-v", "-v", "-v"]);
+}
+
+#[test]
+fn combine_short_flags() {
+    #[derive(Debug, Parser)]
+    struct Flags {
+        #[arg(short, action = clap::ArgAction::Count)]
+        verbose: u8,
+        #[arg(long, action = clap::ArgAction::Count)]
+        debug: u8,
+        #[arg(short, action = clap::ArgAction::Count)]
+        quiet: u8,
+    }
+
+    let app = Flags::command();
+    let localization = Arc::new(Localization::default());
+    let arg_settings = ArgSettings {
+        combine_short_flags: true,
+        ..ArgSettings::default()
+    };
+    let mut app_state = AppState::new(&app, &localization, arg_settings);
+    app_state.args[0].occurrences(3);
+    app_state.args[1].occurrences(2);
+
+    // Long only flags can't be combined
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    assert_eq!(args, ["-vvv", "--debug", "--debug"]);
+}
+
 #[derive(Debug, Parser, PartialEq, Eq)]
 struct ForbidEmpty {
     #[arg(long, value_parser = NonEmptyStringValueParser::new())]
@@ -179,14 +224,1095 @@ fn different_multiple_values() {
     )
 }
 
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct NumArgs {
+    #[arg(long, num_args = 1..=2)]
+    bounded: Vec<String>,
+}
+
+#[test]
+fn num_args() {
+    test_app(
+        |args| args[0].enter_multiple(["a", "b"]),
+        NumArgs {
+            bounded: vec!["a".into(), "b".into()],
+        },
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Terminated {
+    #[arg(long, num_args = 1.., value_terminator = ";")]
+    cmds: Vec<String>,
+    #[arg(required = true, num_args = 1.., value_terminator = ";")]
+    files: Vec<String>,
+    location: String,
+}
+
+#[test]
+fn value_terminator() {
+    test_app(
+        |args| {
+            args[0].enter_multiple(["find", "f"]);
+            args[1].enter_multiple(["a", "b"]);
+            args[2].enter("home");
+        },
+        Terminated {
+            cmds: vec!["find".into(), "f".into()],
+            files: vec!["a".into(), "b".into()],
+            location: "home".into(),
+        },
+    );
+}
+
+#[test]
+fn num_args_too_many() {
+    let app = NumArgs::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    app_state.args[0].enter_multiple(["a", "b", "c"]);
+    assert!(app_state.get_cmd_args(vec![]).is_err());
+}
+
+#[cfg(feature = "json-schema")]
+#[test]
+fn json_schema() {
+    let schema = serde_json::json!({
+        "properties": {
+            "name": { "type": "string" },
+            "count": { "type": "integer", "default": 2 },
+            "loud": { "type": "boolean" },
+            "tags": { "type": "array", "items": { "enum": ["a", "b"] } }
+        },
+        "required": ["name"]
+    });
+
+    // The form of run_app_from_schema is built from the generated command
+    let app = crate::json_schema::command(&schema);
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    for arg in &mut app_state.args {
+        match arg.id.as_str() {
+            "name" => arg.enter("klask"),
+            "loud" => arg.set(),
+            "tags" => arg.enter_multiple(["b"]),
+            _ => {}
+        }
+    }
+
+    let cmd_args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(cmd_args).unwrap();
+
+    assert_eq!(
+        crate::json_schema::to_value(&schema, &matches),
+        serde_json::json!({ "count": 2, "loud": true, "name": "klask", "tags": ["b"] })
+    );
+}
+
+#[cfg(feature = "print-args")]
+#[derive(Debug, Parser)]
+struct PrintArgs {
+    #[arg(long)]
+    name: String,
+    #[arg(long)]
+    nick: Option<String>,
+    #[arg(long)]
+    loud: bool,
+    #[arg(short, action = clap::ArgAction::Count)]
+    verbose: u8,
+    #[arg(long, num_args = 1..)]
+    tags: Vec<String>,
+}
+
+#[cfg(feature = "print-args")]
+#[test]
+fn print_args() {
+    let mut args = vec![
+        "_name",
+        "--klask-print-args",
+        "--name",
+        "klask",
+        "-vv",
+        "--tags",
+        "a",
+    ]
+    .into_iter()
+    .map(Into::into)
+    .collect();
+    assert!(crate::print_args::take_flag(&mut args));
+    assert!(!crate::print_args::take_flag(&mut args));
+
+    let mut app = PrintArgs::command();
+    let matches = app.try_get_matches_from_mut(args).unwrap();
+    assert_eq!(
+        crate::print_args::to_value(&app, &matches),
+        serde_json::json!({ "name": "klask", "loud": false, "verbose": 2, "tags": ["a"] })
+    );
+}
+
+#[cfg(feature = "randomize")]
+#[derive(Debug, Parser)]
+struct Randomized {
+    #[arg(long, value_parser = ["fast", "slow"])]
+    mode: String,
+    #[arg(long, num_args = 1..=2)]
+    list: Vec<String>,
+    #[arg(long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+#[cfg(feature = "randomize")]
+#[test]
+fn randomize() {
+    use rand::SeedableRng;
+
+    let app = Randomized::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    let rng = &mut rand::rngs::StdRng::seed_from_u64(0);
+
+    for _ in 0..20 {
+        app_state.randomize(rng);
+        let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+        let matches = app.clone().try_get_matches_from(args.iter()).unwrap();
+        let randomized = Randomized::from_arg_matches(&matches).unwrap();
+        assert!(["fast", "slow"].contains(&randomized.mode.as_str()));
+        assert!((1..=2).contains(&randomized.list.len()));
+        assert!(randomized.verbose <= 5);
+    }
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+#[command(group(ArgGroup::new("mode").args(["fast", "slow"])))]
+struct Exclusive {
+    #[arg(long)]
+    fast: bool,
+    #[arg(long)]
+    slow: bool,
+}
+
+#[test]
+fn exclusive_group() {
+    let app = Exclusive::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(!app_state.args[0].enabled && !app_state.args[1].enabled);
+
+    app_state.select_in_group(0, Some(0));
+    app_state.args[0].set();
+    app_state.select_in_group(0, Some(1));
+    app_state.args[1].set();
+
+    let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(args.iter()).unwrap();
+    assert_eq!(
+        Exclusive::from_arg_matches(&matches).unwrap(),
+        Exclusive {
+            fast: false,
+            slow: true
+        }
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Template {
+    #[arg(long, default_value = "/home/{{KLASK_TEST_USER}}/results")]
+    output: String,
+    #[arg(long, default_value = "{{KLASK_TEST_MISSING}}")]
+    missing: String,
+}
+
+#[test]
+fn template_env_var() {
+    std::env::set_var("KLASK_TEST_USER", "klask");
+    test_app(
+        |_| {},
+        Template {
+            output: "/home/klask/results".into(),
+            missing: "".into(),
+        },
+    );
+}
+
+#[test]
+fn template_settings_override() {
+    std::env::set_var("KLASK_TEST_USER", "klask");
+    let template_vars = HashMap::from([("KLASK_TEST_USER".to_string(), "override".to_string())]);
+    let settings = ArgSettings {
+        template_vars: Some(Arc::new(template_vars)),
+        ..Default::default()
+    };
+
+    let app = Template::command();
+    let localization = Arc::new(Localization::default());
+    let app_state = AppState::new(&app, &localization, settings);
+    let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(args.iter()).unwrap();
+    assert_eq!(
+        Template::from_arg_matches(&matches).unwrap().output,
+        "/home/override/results"
+    );
+}
+
+#[test]
+fn template_missing() {
+    std::env::set_var("KLASK_TEST_USER", "klask");
+    let app = Template::command();
+    let localization = Arc::new(Localization::default());
+    let app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.args[0].unresolved_template_vars.is_empty());
+    assert_eq!(
+        app_state.args[1].unresolved_template_vars,
+        vec!["KLASK_TEST_MISSING".to_string()]
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct FromEnv {
+    #[arg(long, env = "KLASK_TEST_REQUIRED")]
+    required: String,
+    #[arg(long, env = "KLASK_TEST_LIST", value_delimiter = ',')]
+    list: Vec<String>,
+    #[arg(long, env = "KLASK_TEST_FLAG")]
+    flag: bool,
+}
+
+#[test]
+fn from_env() {
+    std::env::set_var("KLASK_TEST_REQUIRED", "a");
+    std::env::set_var("KLASK_TEST_LIST", "b,c");
+    std::env::set_var("KLASK_TEST_FLAG", "true");
+
+    let app = FromEnv::command();
+    let localization = Arc::new(Localization::default());
+    let app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.args[0].optional);
+    assert!(app_state.args.iter().all(|arg| arg.is_from_env()));
+
+    test_app(
+        |args| args[0].enter("changed"),
+        FromEnv {
+            required: "changed".into(),
+            list: vec!["b".into(), "c".into()],
+            flag: true,
+        },
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Conflicts {
+    #[arg(long, conflicts_with = "file")]
+    stdin: bool,
+    #[arg(long)]
+    file: Option<String>,
+}
+
+#[test]
+fn conflicts() {
+    let app = Conflicts::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.conflicting_arg(1).is_none());
+
+    app_state.args[0].set();
+    assert_eq!(app_state.conflicting_arg(1).unwrap().id, "stdin");
+    assert!(app_state.conflicting_arg(0).is_none());
+
+    // Conflicts go both ways
+    app_state.args[1].enter("a");
+    app_state.args[0].reset();
+    assert_eq!(app_state.conflicting_arg(0).unwrap().id, "file");
+    let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(args.iter()).unwrap();
+    assert_eq!(
+        Conflicts::from_arg_matches(&matches).unwrap(),
+        Conflicts {
+            stdin: false,
+            file: Some("a".into())
+        }
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Json {
+    #[arg(long)]
+    name: String,
+    #[arg(long, default_value = "x")]
+    default: String,
+    #[arg(long)]
+    list: Vec<String>,
+    #[arg(long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    #[arg(long)]
+    flag: bool,
+    #[command(subcommand)]
+    sub: JsonSub,
+}
+
+#[derive(Debug, clap::Subcommand, PartialEq, Eq)]
+enum JsonSub {
+    Build {
+        #[arg(long)]
+        release: bool,
+    },
+}
+
+#[test]
+fn to_json() {
+    let app = Json::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    app_state.args[0].enter("a \"quoted\"\n");
+    app_state.args[2].enter_multiple(["b", "c"]);
+    app_state.args[3].occurrences(2);
+
+    assert_eq!(
+        app_state.to_json(),
+        r#"{"args": {"name": "a \"quoted\"\n", "default": "x", "list": ["b", "c"], "verbose": 2, "flag": false}, "subcommand": {"name": "build", "args": {"release": false}, "subcommand": null}}"#
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct ValueNames {
+    #[arg(long, value_name = "PORT")]
+    port: u16,
+    #[arg(long, value_name = "HOST")]
+    host: Option<String>,
+    #[arg(long, value_name = "LEVEL", default_value = "1")]
+    level: u8,
+    #[arg(long, value_name = "FILE")]
+    files: Vec<String>,
+}
+
+#[test]
+fn value_name_hint() {
+    let app = ValueNames::command();
+    let localization = Arc::new(Localization::default());
+    let app_state = AppState::new(&app, &localization, ArgSettings::default());
+    let hints: Vec<String> = app_state.args.iter().map(|arg| arg.hint_text()).collect();
+    assert_eq!(hints, ["PORT", &localization.optional, "1", "FILE"]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Hidden {
+    #[arg(long, value_parser = ["a", "b"], hide_possible_values = true)]
+    choice: String,
+    #[arg(long, default_value = "secret", hide_default_value = true)]
+    token: String,
+}
+
+#[test]
+fn hidden_possible_values_and_default() {
+    let app = Hidden::command();
+    let localization = Arc::new(Localization::default());
+    let app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.args.iter().all(|arg| match &arg.kind {
+        ArgKind::String { possible, .. } => possible.is_empty(),
+        _ => false,
+    }));
+    let hints: Vec<String> = app_state.args.iter().map(|arg| arg.hint_text()).collect();
+    assert_eq!(hints, ["CHOICE", &localization.optional]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Validators {
+    #[arg(long)]
+    name: String,
+    #[arg(long)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn validators() {
+    let app = Validators::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    let mut validators: HashMap<String, Validator> = HashMap::new();
+    // Closures can capture their configuration
+    let prefix = String::from("#");
+    validators.insert(
+        "tags".into(),
+        Validator::new(move |v| match v.starts_with(&prefix) {
+            true => Ok(()),
+            false => Err(format!("Tags start with {prefix}")),
+        }),
+    );
+
+    app_state.args[0].enter("a");
+    app_state.args[1].enter_multiple(["#b", "c"]);
+    match app_state.validate(&validators) {
+        Err(ExecutionError::ValidationError {
+            path,
+            name,
+            message,
+        }) => {
+            assert!(path.is_empty());
+            assert_eq!(name, "Tags");
+            assert_eq!(message, "Tags start with #");
+        }
+        other => panic!("Expected a validation error, got {other:?}"),
+    }
+
+    app_state.args[1].enter_multiple(["#b", "#c"]);
+    assert!(app_state.validate(&validators).is_ok());
+}
+
+#[derive(Debug, Parser)]
+struct Nested {
+    #[arg(long)]
+    name: Option<String>,
+    #[command(subcommand)]
+    sub: NestedSub,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum NestedSub {
+    Inner {
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+#[test]
+fn validation_error_path() {
+    let app = Nested::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    let mut validators: HashMap<String, Validator> = HashMap::new();
+    validators.insert(
+        "name".into(),
+        Validator::new(|v| match v == "bad" {
+            true => Err("Bad name".into()),
+            false => Ok(()),
+        }),
+    );
+
+    app_state.args[0].enter("good");
+    app_state.subcommands.get_mut("inner").unwrap().args[0].enter("bad");
+    let Err(ExecutionError::ValidationError {
+        path,
+        name,
+        message,
+    }) = app_state.validate(&validators)
+    else {
+        panic!("Expected a validation error");
+    };
+    assert_eq!(path, ["inner"]);
+
+    // Only the arg in the subcommand gets the error, not the one with the same name above it
+    app_state.update_validation_error(&path, &name, &message);
+    assert_eq!(app_state.args[0].validation_error, None);
+    let inner = &app_state.subcommands["inner"];
+    assert_eq!(inner.args[0].validation_error.as_deref(), Some("Bad name"));
+
+    app_state.update_validation_error(&[], "", "");
+    assert_eq!(
+        app_state.subcommands["inner"].args[0].validation_error,
+        None
+    );
+}
+
+#[test]
+fn changed_values() {
+    let app = Json::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.get_changed_values(vec![]).is_empty());
+
+    app_state.args[0].enter("a");
+    app_state.args[2].enter_multiple(["b", "c"]);
+    assert_eq!(
+        app_state.get_changed_values(vec![]),
+        [
+            ("Name".to_string(), "a".to_string()),
+            ("List".to_string(), "b, c".to_string())
+        ]
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct DisplayValues {
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long, default_value = "1")]
+    level: u8,
+    #[arg(long)]
+    list: Vec<String>,
+    #[arg(long)]
+    flag: bool,
+    #[arg(long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    #[arg(long)]
+    color: Option<bool>,
+}
+
+#[test]
+fn display_values() {
+    let app = DisplayValues::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    let values = |app_state: &AppState| {
+        app_state
+            .args
+            .iter()
+            .map(|arg| arg.get_display_value())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        values(&app_state),
+        ["(empty)", "(default)", "(empty)", "✗", "0", "(empty)"]
+    );
+
+    app_state.args[0].enter("a");
+    app_state.args[1].enter("2");
+    app_state.args[2].enter_multiple(["b", "c", "d", "e"]);
+    app_state.args[3].set();
+    app_state.args[4].occurrences(2);
+    app_state.args[5].set_optional_bool(Some(false));
+    assert_eq!(values(&app_state), ["a", "2", "b, c, d, …", "✓", "2", "✗"]);
+}
+
+#[test]
+fn changed_args() {
+    let app = DisplayValues::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.get_changed_args(vec![]).is_empty());
+
+    // Typing the default isn't a change
+    app_state.args[1].enter("1");
+    app_state.args[3].set();
+    assert_eq!(app_state.get_changed_args(vec![]), ["flag"]);
+
+    app_state.args[1].enter("2");
+    assert_eq!(app_state.get_changed_args(vec![]), ["level", "flag"]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Missing {
+    #[arg(long)]
+    optional: Option<String>,
+    #[arg(long, default_value = "a")]
+    with_default: String,
+    #[arg(long)]
+    name: String,
+    #[arg(long, required = true)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn focus_first_missing() {
+    let app = Missing::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    let scroll_to = |app_state: &AppState| {
+        app_state
+            .args
+            .iter()
+            .map(|arg| arg.scroll_to)
+            .collect::<Vec<_>>()
+    };
+
+    assert!(app_state.focus_first_missing());
+    assert_eq!(scroll_to(&app_state), [false, false, true, false]);
+
+    app_state.args[2].scroll_to = false;
+    app_state.args[2].enter("b");
+    assert!(app_state.focus_first_missing());
+    assert_eq!(scroll_to(&app_state), [false, false, false, true]);
+
+    app_state.args[3].enter_multiple(["c"]);
+    assert!(!app_state.has_missing());
+}
+
+#[test]
+fn focused_arg() {
+    let app = Missing::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.focused_arg().is_none());
+
+    app_state.args[2].focused_at = Some(2.0);
+    app_state.args[0].focused_at = Some(1.0);
+    assert_eq!(app_state.focused_arg().unwrap().id, "name");
+
+    app_state.args[3].focused_at = Some(3.0);
+    assert_eq!(app_state.focused_arg().unwrap().id, "tags");
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+#[command(version)]
+struct HelpAndVersion {
+    #[arg(long)]
+    name: String,
+    #[arg(short = 'H', long, action = clap::ArgAction::Help)]
+    show_help: Option<bool>,
+    #[arg(long = "ver", action = clap::ArgAction::Version)]
+    show_version: Option<bool>,
+}
+
+#[test]
+fn help_and_version_skipped() {
+    // Building adds the automatic `--help` and `--version`
+    let mut app = HelpAndVersion::command();
+    app.build();
+    let localization = Arc::new(Localization::default());
+    let app_state = AppState::new(&app, &localization, ArgSettings::default());
+    let ids: Vec<_> = app_state.args.iter().map(|arg| arg.id()).collect();
+    assert_eq!(ids, ["name"]);
+}
+
+#[test]
+fn subcommand_missing() {
+    let inner = Command::new("inner").subcommand_required(true);
+    let app = Command::new("app")
+        .subcommand_required(true)
+        .subcommand(inner.clone().subcommand(Command::new("leaf")))
+        .subcommand(Command::new("other"));
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(!app_state.subcommand_missing());
+
+    app_state.current = None;
+    assert!(app_state.subcommand_missing());
+
+    // Required in the selected subcommand
+    app_state.current = Some("inner".into());
+    app_state.subcommands.get_mut("inner").unwrap().current = None;
+    assert!(app_state.subcommand_missing());
+
+    let empty = AppState::new(&inner, &localization, ArgSettings::default());
+    assert!(empty.subcommand_missing());
+}
+
+#[test]
+fn compact_mode() {
+    let app = Nested::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    app_state.set_compact_mode(true);
+    assert!(app_state.compact_mode);
+    assert!(app_state.subcommands["inner"].compact_mode);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Conditional {
+    #[arg(long, default_value = "text")]
+    format: String,
+    #[arg(long)]
+    indent_depth: Option<String>,
+}
+
+#[test]
+fn conditional_visibility() {
+    let app = Conditional::command();
+    let localization = Arc::new(Localization::default());
+    let rules = [("format".into(), "json".into(), vec!["indent_depth".into()])];
+    let settings = ArgSettings {
+        conditional_visibility: Arc::new(rules),
+        ..ArgSettings::default()
+    };
+    let mut app_state = AppState::new(&app, &localization, settings);
+
+    app_state.args[0].enter("json");
+    app_state.update_visibility();
+    assert!(app_state.is_visible("indent_depth"));
+    app_state.args[1].enter("4");
+    assert_eq!(
+        app_state.get_cmd_args(vec![]).unwrap(),
+        ["--format", "json", "--indent-depth", "4"]
+    );
+
+    // Hiding clears the value, and the default doesn't trigger
+    app_state.args[0].enter("");
+    app_state.update_visibility();
+    assert!(!app_state.is_visible("indent_depth"));
+    assert!(!app_state.args[1].is_changed());
+    assert!(app_state.is_visible("format"));
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Help {
+    /// Short
+    ///
+    /// Long help
+    #[arg(long)]
+    long: Option<String>,
+    /// Short
+    ///
+    /// Long help
+    #[arg(long, next_line_help = true)]
+    next_line: Option<String>,
+    /// Only short
+    #[arg(long)]
+    short: Option<String>,
+}
+
+#[test]
+fn inline_long_help() {
+    let app = Help::command();
+    let localization = Arc::new(Localization::default());
+    let help = |inline_long_help| {
+        let settings = ArgSettings {
+            inline_long_help,
+            ..Default::default()
+        };
+        let app_state = AppState::new(&app, &localization, settings);
+        app_state
+            .args
+            .iter()
+            .map(|arg| (arg.desc.clone().unwrap(), arg.inline_help.clone()))
+            .collect::<Vec<_>>()
+    };
+    let long = "Short\n\nLong help".to_string();
+
+    assert_eq!(
+        help(false),
+        [
+            (long.clone(), None),
+            ("Short".into(), Some(long.clone())),
+            ("Only short".into(), None)
+        ]
+    );
+    assert_eq!(
+        help(true),
+        [
+            ("Short".into(), Some(long.clone())),
+            ("Short".into(), Some(long)),
+            ("Only short".into(), None)
+        ]
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct External {
+    #[command(subcommand)]
+    sub: ExternalSub,
+}
+
+#[derive(Debug, clap::Subcommand, PartialEq, Eq)]
+#[command(allow_external_subcommands = true)]
+enum ExternalSub {
+    Build,
+    #[command(external_subcommand)]
+    Other(Vec<String>),
+}
+
+#[test]
+fn external_subcommand() {
+    let app = External::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    app_state.current = None;
+    let external = app_state.external.as_mut().unwrap();
+    external.selected = true;
+    assert!(app_state.get_cmd_args(vec![]).is_err());
+
+    let external = app_state.external.as_mut().unwrap();
+    external.name.enter("plugin");
+    external.args.enter_multiple(["--fast", "a"]);
+    let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(args.iter()).unwrap();
+    assert_eq!(
+        External::from_arg_matches(&matches).unwrap(),
+        External {
+            sub: ExternalSub::Other(vec!["plugin".into(), "--fast".into(), "a".into()])
+        }
+    );
+    assert_eq!(
+        app_state.to_json(),
+        r#"{"args": {}, "subcommand": {"name": "plugin", "args": ["--fast", "a"], "subcommand": null}}"#
+    );
+}
+
+#[test]
+fn custom_colors() {
+    let settings = ArgSettings {
+        error_color: Color32::LIGHT_RED,
+        warning_color: Color32::GOLD,
+        ..Default::default()
+    };
+
+    let app = Simple::command();
+    let localization = Arc::new(Localization::default());
+    let app_state = AppState::new(&app, &localization, settings);
+    for arg in &app_state.args {
+        assert_eq!(arg.settings.error_color, Color32::LIGHT_RED);
+        assert_eq!(arg.settings.warning_color, Color32::GOLD);
+    }
+}
+
+#[test]
+fn batch_args() {
+    let app = Json::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    app_state.args[0].enter("a");
+    assert!(app_state.get_batch_cmd_args("list").unwrap().is_empty());
+
+    app_state.args[2].enter_multiple(["b", "c"]);
+    let batch = app_state.get_batch_cmd_args("list").unwrap();
+    let items: Vec<&str> = batch.iter().map(|(item, _)| item.as_str()).collect();
+    assert_eq!(items, ["b", "c"]);
+    assert_eq!(batch[1].1, ["--name", "a", "--list", "c", "build"]);
+    // The form itself is unchanged
+    assert_eq!(app_state.args[2].get_display_value(), "b, c");
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct OptionalBool {
+    #[arg(long)]
+    unset: Option<bool>,
+    #[arg(long)]
+    on: Option<bool>,
+    #[arg(long)]
+    off: Option<bool>,
+}
+
+#[test]
+fn optional_bool() {
+    test_app(
+        |args| {
+            args[1].set_optional_bool(Some(true));
+            args[2].set_optional_bool(Some(false));
+        },
+        OptionalBool {
+            unset: None,
+            on: Some(true),
+            off: Some(false),
+        },
+    );
+}
+
+#[test]
+fn lazy_args() {
+    let app = Json::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state.args.iter().all(|arg| arg.state.get().is_none()));
+
+    // Going through the args creates the selected subcommand's too
+    app_state.update_validation_error(&[], "", "");
+    assert!(app_state.args.iter().all(|arg| arg.state.get().is_some()));
+    let sub = &app_state.subcommands["build"];
+    assert!(sub.args.iter().all(|arg| arg.state.get().is_some()));
+}
+
+#[test]
+fn lazy_subcommands() {
+    let app = Command::new("app")
+        .subcommand(Command::new("build").subcommand(Command::new("deep")))
+        .subcommand(Command::new("run"));
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
+    assert!(app_state
+        .subcommands
+        .values()
+        .all(|s| s.state.get().is_none()));
+
+    // Only the selected subcommands are created
+    assert_eq!(app_state.get_cmd_args(vec![]).unwrap(), ["build", "deep"]);
+    assert!(app_state.subcommands["build"].state.get().is_some());
+    assert!(app_state.subcommands["run"].state.get().is_none());
+
+    // Subcommands created later still get the current mode
+    app_state.set_compact_mode(true);
+    assert!(app_state.subcommands["run"].state.get().is_none());
+    app_state.current = Some("run".into());
+    assert_eq!(app_state.get_cmd_args(vec![]).unwrap(), ["run"]);
+    assert!(app_state.subcommands["run"].compact_mode);
+}
+
+#[derive(Debug, Parser)]
+struct Sorted {
+    #[arg(long)]
+    zeta: Option<String>,
+    #[arg(long)]
+    beta: String,
+    #[arg(long)]
+    alpha: Option<String>,
+    gamma: String,
+}
+
+#[test]
+fn arg_sort() {
+    let app = Sorted::command();
+    let localization = Arc::new(Localization::default());
+    let order = |arg_sort| {
+        let settings = ArgSettings {
+            arg_sort,
+            ..Default::default()
+        };
+        let app_state = AppState::new(&app, &localization, settings);
+        app_state
+            .order
+            .iter()
+            .map(|&i| app_state.args[i].id().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        order(ArgSort::Definition),
+        ["zeta", "beta", "alpha", "gamma"]
+    );
+    assert_eq!(
+        order(ArgSort::Alphabetical),
+        ["alpha", "beta", "gamma", "zeta"]
+    );
+    assert_eq!(
+        order(ArgSort::RequiredFirst),
+        ["beta", "gamma", "zeta", "alpha"]
+    );
+    assert_eq!(
+        order(ArgSort::OptionalFirst),
+        ["zeta", "alpha", "beta", "gamma"]
+    );
+}
+
+#[test]
+fn arg_order() {
+    let app = Sorted::command();
+    let localization = Arc::new(Localization::default());
+    let arg_order = ["gamma".to_string(), "missing".into(), "zeta".into()];
+    let settings = ArgSettings {
+        arg_sort: ArgSort::Alphabetical,
+        arg_order: Arc::new(arg_order),
+        ..Default::default()
+    };
+    let app_state = AppState::new(&app, &localization, settings);
+    let order: Vec<_> = app_state
+        .order
+        .iter()
+        .map(|&i| app_state.args[i].id())
+        .collect();
+    assert_eq!(order, ["gamma", "zeta", "alpha", "beta"]);
+}
+
+#[derive(Debug, Parser)]
+struct History {
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long)]
+    tags: Vec<String>,
+    #[arg(long)]
+    flag: bool,
+}
+
+#[test]
+fn field_history() {
+    let loaded = FieldHistory::parse(
+        r#"# comment
+        "name" = ["old \"quoted\"\n"]
+        "tags" = ["a", "b"]
+        "#,
+    );
+    assert_eq!(
+        loaded.fields["name"],
+        [vec!["old \"quoted\"\n".to_string()]]
+    );
+
+    let app = History::command();
+    let localization = Arc::new(Localization::default());
+    let arg_settings = ArgSettings {
+        history: Some(Arc::new(loaded.clone())),
+        ..ArgSettings::default()
+    };
+    let mut app_state = AppState::new(&app, &localization, arg_settings);
+    assert_eq!(app_state.args[1].history, [vec!["a", "b"]]);
+
+    for i in 0..12 {
+        app_state.args[0].enter(&format!("value {}", i % 11));
+        app_state.args[1].enter_multiple(["a", "b"]);
+        app_state.args[2].set();
+        app_state.record_history();
+    }
+
+    // Repeated values move to the front and only the last few are kept
+    let name_history = &app_state.args[0].history;
+    assert_eq!(name_history.len(), crate::field_history::MAX_ENTRIES);
+    assert_eq!(name_history[0], ["value 0"]);
+    assert_eq!(name_history[1], ["value 10"]);
+    assert_eq!(app_state.args[1].history, [vec!["a", "b"]]);
+    assert!(app_state.args[2].history.is_empty());
+
+    let mut history = loaded.clone();
+    app_state.collect_history(&mut history);
+    let path = std::env::temp_dir().join(format!("klask-history-{}", Uuid::new_v4()));
+    history.save(&path).unwrap();
+    assert_eq!(FieldHistory::load(&path), history);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "man")]
+#[test]
+fn man_page() {
+    let app = clap::Command::new("example")
+        .about("Does things")
+        .arg(clap::arg!(--name <NAME> "Who to greet"));
+    let page = crate::render_man_page(&app).unwrap();
+    assert!(page.starts_with(".ie"), "{page}");
+    assert!(page.contains(".TH example 1"));
+    assert!(page.contains("Who to greet"));
+}
+
+#[test]
+fn infer_path_from_name() {
+    #[derive(Debug, Parser)]
+    struct Paths {
+        #[arg(long)]
+        config_file: String,
+        #[arg(long)]
+        output_dir: Vec<String>,
+        #[arg(long)]
+        cache_path: String,
+        #[arg(long, value_hint = ValueHint::Url)]
+        upload_file: String,
+        #[arg(long)]
+        name: String,
+    }
+
+    let value_hints = |infer_path_from_name| {
+        let app = Paths::command();
+        let localization = Arc::new(Localization::default());
+        let arg_settings = ArgSettings {
+            infer_path_from_name,
+            ..ArgSettings::default()
+        };
+        let app_state = AppState::new(&app, &localization, arg_settings);
+        app_state
+            .args
+            .iter()
+            .map(|arg| match &arg.kind {
+                ArgKind::String { value_hint, .. }
+                | ArgKind::MultipleStrings { value_hint, .. } => *value_hint,
+                _ => panic!(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        value_hints(true),
+        [
+            ValueHint::FilePath,
+            ValueHint::DirPath,
+            ValueHint::AnyPath,
+            ValueHint::Url,
+            ValueHint::Unknown
+        ]
+    );
+    assert!(value_hints(false)
+        .iter()
+        .all(|&hint| matches!(hint, ValueHint::Unknown | ValueHint::Url)));
+}
+
 fn test_app<C, F>(setup: F, expected: C)
 where
     C: CommandFactory + FromArgMatches + Debug + Eq,
-    F: FnOnce(&mut Vec<ArgState>),
+    F: FnOnce(&mut Vec<LazyArgState>),
 {
     let app = C::command();
-    let localization = Localization::default();
-    let mut app_state = AppState::new(&app, &localization);
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, &localization, ArgSettings::default());
     setup(&mut app_state.args);
     let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
     eprintln!("Args: {:?}", &args[1..]);
@@ -195,13 +1321,13 @@ where
     assert_eq!(c, expected);
 }
 
-fn enter_consecutive<const N: usize>(args: &mut [ArgState], vals: [&str; N]) {
+fn enter_consecutive<const N: usize>(args: &mut [LazyArgState], vals: [&str; N]) {
     for i in 0..N {
         args[i].enter(vals[i]);
     }
 }
 
-impl crate::arg_state::ArgState<'_> {
+impl crate::arg_state::ArgState {
     fn enter(&mut self, val: &str) {
         if let ArgKind::String { value, .. } = &mut self.kind {
             value.0 = val.to_string();
@@ -236,4 +1362,12 @@ impl crate::arg_state::ArgState<'_> {
             panic!("Called set on {:?}", self)
         }
     }
+
+    fn set_optional_bool(&mut self, val: Option<bool>) {
+        if let ArgKind::OptionalBool(b) = &mut self.kind {
+            *b = val;
+        } else {
+            panic!("Called set_optional_bool on {:?}", self)
+        }
+    }
 }
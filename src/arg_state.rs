@@ -1,20 +1,119 @@
-use crate::{settings::Localization, to_sentence_case, Klask};
-use clap::{Arg, ValueHint};
-use eframe::egui::{widgets::Widget, ComboBox, Response, TextEdit, Ui};
+use crate::{
+    field_history::{self, FieldHistory},
+    settings::{ArgSort, Localization, SubcommandDisplay},
+    to_sentence_case, Klask,
+};
+use clap::{builder::ValueRange, Arg, ValueHint};
+use eframe::egui::{
+    self, widgets::Widget, Align, Button, Checkbox, Color32, ComboBox, Frame, Id, Key, Response,
+    Stroke, TextEdit, Ui,
+};
 use rfd::FileDialog;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
-pub struct ArgState<'s> {
+pub struct ArgState {
+    pub id: String,
     pub name: String,
     pub call_name: Option<String>,
+    /// Environment variable that can set this arg, from [`Arg::env`]
+    pub env: Option<String>,
+    /// Values the environment variable had when the GUI started, split
+    /// by the value delimiter for multiple values
+    pub env_values: Vec<String>,
+    /// Shown when hovering over the name
     pub desc: Option<String>,
+    /// Long help shown below the value, see [`ArgSettings::inline_long_help`]
+    pub inline_help: Option<String>,
+    /// Names of the values from [`Arg::value_name`], shown as hint text
+    pub value_names: Vec<String>,
     pub optional: bool,
     pub use_equals: bool,
+    /// Passed after the values of multiple value args, from [`Arg::value_terminator`]
+    pub value_terminator: Option<String>,
     pub forbid_empty: bool,
     pub kind: ArgKind,
     pub validation_error: Option<String>,
-    pub localization: &'s Localization,
+    /// Disabled args can't be edited and aren't passed to the program
+    pub enabled: bool,
+    /// Name of a set arg this one conflicts with, which also disables it.
+    /// Updated by [`crate::app_state::AppState`] every frame.
+    pub conflicts_with_set: Option<String>,
+    /// The default had `{{KEY}}` placeholders. Clap only knows the unexpanded
+    /// default, so the expanded one is passed explicitly.
+    pub expanded_default: bool,
+    /// From [`Arg::hide_default_value`], the default isn't shown as hint text
+    pub hide_default: bool,
+    /// Placeholder keys that weren't found and were replaced with an empty string
+    pub unresolved_template_vars: Vec<String>,
+    /// Scrolled to and focused the next time it's shown,
+    /// from [`crate::app_state::AppState::focus_first_missing`]
+    pub scroll_to: bool,
+    /// Values used in previous runs, most recent first. Single values are
+    /// one element entries, multiple values are kept together.
+    pub history: VecDeque<Vec<String>>,
+    /// Time its text field was last focused or its row clicked, for
+    /// [`crate::Settings::show_help_panel`]
+    pub focused_at: Option<f64>,
+    pub localization: Arc<Localization>,
+    pub settings: ArgSettings,
+}
+
+/// Parts of [`crate::Settings`] that affect how arguments are created and displayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgSettings {
+    pub inline_validation_errors: bool,
+    /// Show the long help below the value instead of when hovering, also set
+    /// by [`Arg::next_line_help`] and [`clap::Command::next_line_help`]
+    pub inline_long_help: bool,
+    pub error_color: Color32,
+    pub warning_color: Color32,
+    pub disable_file_dialogs: bool,
+    pub template_vars: Option<Arc<HashMap<String, String>>>,
+    pub arg_sort: ArgSort,
+    /// From [`crate::Settings::arg_order`]
+    pub arg_order: Arc<[String]>,
+    /// From [`crate::Settings::conditional_visibility`]
+    pub conditional_visibility: Arc<[(String, String, Vec<String>)]>,
+    /// From [`crate::Settings::combine_short_flags`]
+    pub combine_short_flags: bool,
+    /// From [`crate::Settings::infer_path_from_name`]
+    pub infer_path_from_name: bool,
+    /// From [`crate::Settings::subcommand_display`]
+    pub subcommand_display: SubcommandDisplay,
+    /// From [`crate::Settings::compact_mode`]
+    pub compact_mode: bool,
+    /// Prefix of the environment variables that can be pasted into text fields,
+    /// None when [`crate::Settings::enable_env_paste`] is off
+    pub env_paste: Option<Arc<str>>,
+    /// Loaded from [`crate::Settings::field_history_path`]
+    pub history: Option<Arc<FieldHistory>>,
+}
+
+impl Default for ArgSettings {
+    fn default() -> Self {
+        Self {
+            inline_validation_errors: true,
+            inline_long_help: false,
+            error_color: Color32::RED,
+            warning_color: Color32::YELLOW,
+            disable_file_dialogs: false,
+            template_vars: None,
+            arg_sort: ArgSort::Definition,
+            arg_order: Arc::default(),
+            conditional_visibility: Arc::default(),
+            combine_short_flags: false,
+            infer_path_from_name: false,
+            subcommand_display: SubcommandDisplay::Dropdown,
+            compact_mode: false,
+            env_paste: None,
+            history: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -30,61 +129,151 @@ pub enum ArgKind {
         default: Vec<String>,
         possible: Vec<String>,
         value_hint: ValueHint,
+        /// Allowed number of values, from `num_args`
+        num_args: Option<ValueRange>,
     },
     Occurences(u8),
     Bool(bool),
+    /// Flag that takes a `true` or `false` value and can also be left out, like `Option<bool>`
+    OptionalBool(Option<bool>),
 }
 
-impl<'s> ArgState<'s> {
-    pub fn new(arg: &Arg, localization: &'s Localization) -> Self {
-        let default: Vec<String> = arg
+impl ArgState {
+    /// Default values can contain `{{KEY}}` placeholders, which are replaced with
+    /// `settings.template_vars[KEY]` or else the `KEY` environment variable. Unresolved
+    /// placeholders are replaced with an empty string and the arg shows a warning.
+    /// The expanded default is what gets passed to the program.
+    ///
+    /// Args with [`Arg::env`] are filled with the value of the environment variable
+    /// when it's set, and are treated as optional.
+    pub fn new(arg: &Arg, localization: &Arc<Localization>, settings: ArgSettings) -> Self {
+        let mut unresolved_template_vars = vec![];
+        let raw_default: Vec<&str> = arg
             .get_default_values()
             .iter()
             .filter_map(|v| v.to_str())
-            .map(String::from)
             .collect();
+        let default: Vec<String> = raw_default
+            .iter()
+            .map(|v| {
+                expand_template(
+                    v,
+                    settings.template_vars.as_deref(),
+                    &mut unresolved_template_vars,
+                )
+            })
+            .collect();
+        let expanded_default = !default.iter().eq(raw_default.iter());
+
+        let env = arg.get_env().map(|env| env.to_string_lossy().into_owned());
+        let env_value = env.as_deref().and_then(|env| std::env::var(env).ok());
+        let env_values: Vec<String> = match (&env_value, arg.get_value_delimiter()) {
+            (Some(value), Some(delimiter))
+                if matches!(arg.get_action(), clap::ArgAction::Append) =>
+            {
+                value.split(delimiter).map(String::from).collect()
+            }
+            (value, _) => value.iter().cloned().collect(),
+        };
+
+        // Hidden possible values get a free text field instead of the combo box
+        let help = arg.get_help().map(ToString::to_string);
+        let long_help = arg.get_long_help().map(ToString::to_string);
+        let inline_long_help = settings.inline_long_help || arg.is_next_line_help_set();
+        let (desc, inline_help) = match long_help {
+            Some(long_help) if inline_long_help => (help, Some(long_help)),
+            long_help => (long_help.or(help), None),
+        };
 
         let mut possible = vec![];
         if let Some(values) = arg.get_value_parser().possible_values() {
-            possible = values.map(|v| v.get_name().to_string()).collect();
+            if !arg.is_hide_possible_values_set() {
+                possible = values.map(|v| v.get_name().to_string()).collect();
+            }
         };
 
+        let value_hint = match arg.get_value_hint() {
+            ValueHint::Unknown if settings.infer_path_from_name => {
+                infer_value_hint(arg.get_id().as_str())
+            }
+            value_hint => value_hint,
+        };
+
+        // Only optional ones, a required flag would need a value anyway
+        let is_optional_bool = !arg.is_required_set()
+            && arg.get_value_parser().type_id() == (&false).into()
+            && matches!(arg.get_num_args(), None | Some(ValueRange::SINGLE));
+
         let kind = match *arg.get_action() {
+            clap::ArgAction::Set if is_optional_bool => {
+                ArgKind::OptionalBool(env_value.as_deref().map(is_truthy))
+            }
             clap::ArgAction::Set => ArgKind::String {
-                value: (String::new(), Uuid::new_v4()),
-                default: default.get(0).map(|v| v.to_string()),
+                value: (env_value.clone().unwrap_or_default(), Uuid::new_v4()),
+                default: default.first().map(|v| v.to_string()),
                 possible,
-                value_hint: arg.get_value_hint(),
+                value_hint,
             },
             clap::ArgAction::Append => ArgKind::MultipleStrings {
-                values: vec![],
+                values: env_values
+                    .iter()
+                    .map(|v| (v.clone(), Uuid::new_v4()))
+                    .collect(),
                 default,
                 possible,
-                value_hint: arg.get_value_hint(),
+                value_hint,
+                num_args: arg.get_num_args(),
             },
-            clap::ArgAction::SetTrue => ArgKind::Bool(false),
+            clap::ArgAction::SetTrue => ArgKind::Bool(env_value.as_deref().is_some_and(is_truthy)),
             clap::ArgAction::SetFalse => ArgKind::Bool(true),
             clap::ArgAction::Count => ArgKind::Occurences(0),
             _ => ArgKind::Bool(false),
         };
 
+        let long = arg.get_long().map(|s| format!("--{s}"));
+        let short = arg.get_short().map(|c| format!("-{c}"));
+        // Counted flags are conventionally repeated in the short form, like `-v -v`
+        let call_name = match kind {
+            ArgKind::Occurences(_) => short.or(long),
+            _ => long.or(short),
+        };
+
         Self {
+            id: arg.get_id().to_string(),
             name: to_sentence_case(arg.get_id().as_ref()),
-            call_name: arg
-                .get_long()
-                .map(|s| format!("--{s}"))
-                .or_else(|| arg.get_short().map(|c| format!("-{c}"))),
-            desc: arg
-                .get_long_help()
+            env,
+            call_name,
+            desc,
+            inline_help,
+            value_names: arg
+                .get_value_names()
+                .unwrap_or_default()
+                .iter()
                 .map(ToString::to_string)
-                .or_else(|| arg.get_help().map(ToString::to_string)),
-            optional: !arg.is_required_set(),
+                .collect(),
+            optional: !arg.is_required_set() || env_value.is_some(),
             use_equals: arg.is_require_equals_set(),
+            value_terminator: arg.get_value_terminator().map(ToString::to_string),
             // TODO: catch forbid empty from arg?
             forbid_empty: false,
             kind,
             validation_error: None,
-            localization,
+            env_values,
+            enabled: true,
+            conflicts_with_set: None,
+            expanded_default,
+            hide_default: arg.is_hide_default_value_set(),
+            unresolved_template_vars,
+            scroll_to: false,
+            history: settings
+                .history
+                .as_ref()
+                .and_then(|history| history.fields.get(arg.get_id().as_str()))
+                .cloned()
+                .unwrap_or_default(),
+            focused_at: None,
+            localization: Arc::clone(localization),
+            settings,
         }
     }
 
@@ -92,20 +281,297 @@ impl<'s> ArgState<'s> {
         self.validation_error = (self.name == name).then(|| message.to_string());
     }
 
+    /// Clears the value, so the arg won't be passed on the command line.
+    pub fn reset(&mut self) {
+        match &mut self.kind {
+            ArgKind::String { value, .. } => value.0.clear(),
+            ArgKind::MultipleStrings { values, .. } => values.clear(),
+            ArgKind::Occurences(i) => *i = 0,
+            ArgKind::Bool(bool) => *bool = false,
+            ArgKind::OptionalBool(bool) => *bool = None,
+        }
+    }
+
+    /// Fills in a random value that could plausibly be entered, for exploratory testing.
+    /// Strings are picked from the possible values or are random alphanumeric text.
+    #[cfg(feature = "randomize")]
+    pub fn randomize_value(&mut self, rng: &mut impl rand::Rng) {
+        fn random_string(rng: &mut impl rand::Rng, possible: &[String]) -> String {
+            use rand::{distributions::Alphanumeric, seq::SliceRandom};
+            match possible.choose(rng) {
+                Some(value) => value.clone(),
+                None => {
+                    let len = rng.gen_range(1..=12);
+                    (0..len)
+                        .map(|_| char::from(rng.sample(Alphanumeric)))
+                        .collect()
+                }
+            }
+        }
+
+        match &mut self.kind {
+            ArgKind::String {
+                value, possible, ..
+            } => value.0 = random_string(rng, possible),
+            ArgKind::MultipleStrings {
+                values,
+                possible,
+                num_args,
+                ..
+            } => {
+                let mut count = rng.gen_range(1..=3);
+                if let Some(num_args) = num_args {
+                    count = count.clamp(num_args.min_values(), num_args.max_values());
+                }
+                *values = (0..count)
+                    .map(|_| (random_string(rng, possible), Uuid::new_v4()))
+                    .collect();
+            }
+            ArgKind::Occurences(i) => *i = rng.gen_range(0..=5),
+            ArgKind::Bool(bool) => *bool = rng.gen(),
+            ArgKind::OptionalBool(bool) => {
+                *bool = [None, Some(true), Some(false)][rng.gen_range(0..3)]
+            }
+        }
+    }
+
+    /// Whether the user set a value other than the default that will be passed on the
+    /// command line.
+    pub fn is_changed(&self) -> bool {
+        match &self.kind {
+            ArgKind::String {
+                value: (value, _),
+                default,
+                ..
+            } => !value.is_empty() && default.as_ref() != Some(value),
+            ArgKind::MultipleStrings {
+                values, default, ..
+            } => !values.is_empty() && !values.iter().map(|(v, _)| v).eq(default.iter()),
+            &ArgKind::Occurences(i) => i > 0,
+            &ArgKind::Bool(bool) => bool,
+            ArgKind::OptionalBool(bool) => bool.is_some(),
+        }
+    }
+
+    /// Required and enabled, but without a value or default, so clap would reject it.
+    pub fn is_missing(&self) -> bool {
+        if self.optional || !self.enabled {
+            return false;
+        }
+        match &self.kind {
+            ArgKind::String {
+                value: (value, _),
+                default,
+                ..
+            } => value.is_empty() && default.is_none(),
+            ArgKind::MultipleStrings {
+                values, default, ..
+            } => values.is_empty() && default.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Adds the current value to the front of [`ArgState::history`]. Empty values
+    /// and args without text fields are skipped.
+    pub fn record_history(&mut self) {
+        let entry = match &self.kind {
+            ArgKind::String {
+                value: (value, _), ..
+            } if !value.is_empty() => vec![value.clone()],
+            ArgKind::MultipleStrings { values, .. } if !values.is_empty() => {
+                values.iter().map(|(v, _)| v.clone()).collect()
+            }
+            _ => return,
+        };
+        field_history::push(&mut self.history, entry);
+    }
+
+    /// Whether the current value is the one read from the environment variable.
+    pub fn is_from_env(&self) -> bool {
+        match &self.kind {
+            ArgKind::String {
+                value: (value, _), ..
+            } => self.env_values.first() == Some(value),
+            ArgKind::MultipleStrings { values, .. } => {
+                !values.is_empty() && values.iter().map(|(v, _)| v).eq(self.env_values.iter())
+            }
+            &ArgKind::Bool(bool) => bool && !self.env_values.is_empty(),
+            ArgKind::OptionalBool(bool) => bool.is_some() && !self.env_values.is_empty(),
+            ArgKind::Occurences(_) => false,
+        }
+    }
+
+    /// Whether `value` is the entered value (or the default when there is none), or
+    /// one of them for args with multiple values. Flags are `true` or `false` and
+    /// counted flags their count.
+    pub fn has_value(&self, value: &str) -> bool {
+        match &self.kind {
+            ArgKind::String {
+                value: (current, _),
+                default,
+                ..
+            } => match (current.is_empty(), default) {
+                (true, Some(default)) => default == value,
+                _ => current == value,
+            },
+            ArgKind::MultipleStrings {
+                values, default, ..
+            } => match values.is_empty() {
+                true => default.iter().any(|v| v == value),
+                false => values.iter().any(|(v, _)| v == value),
+            },
+            ArgKind::Occurences(i) => i.to_string() == value,
+            ArgKind::Bool(bool) | ArgKind::OptionalBool(Some(bool)) => bool.to_string() == value,
+            ArgKind::OptionalBool(None) => false,
+        }
+    }
+
+    /// Short summary of the entered value for lists like the changes. Multiple values
+    /// are separated by commas and cut off after three, flags are a check mark or a
+    /// cross. Empty values show whether the default is used.
+    pub fn get_display_value(&self) -> String {
+        let localization = &*self.localization;
+        match &self.kind {
+            ArgKind::String {
+                value: (value, _),
+                default,
+                ..
+            } => match (value.is_empty(), default) {
+                (false, _) => value.clone(),
+                (true, Some(_)) => localization.value_default.clone(),
+                (true, None) => localization.value_empty.clone(),
+            },
+            ArgKind::MultipleStrings {
+                values, default, ..
+            } => match (values.is_empty(), default.is_empty()) {
+                (false, _) => {
+                    let mut shown: Vec<&str> =
+                        values.iter().take(3).map(|(v, _)| v.as_str()).collect();
+                    if values.len() > 3 {
+                        shown.push("…");
+                    }
+                    shown.join(", ")
+                }
+                (true, false) => localization.value_default.clone(),
+                (true, true) => localization.value_empty.clone(),
+            },
+            ArgKind::Occurences(i) => i.to_string(),
+            ArgKind::Bool(bool) | ArgKind::OptionalBool(Some(bool)) => match bool {
+                true => "✓".into(),
+                false => "✗".into(),
+            },
+            ArgKind::OptionalBool(None) => localization.value_empty.clone(),
+        }
+    }
+
+    /// The entered value as JSON, falling back to the default. Strings are
+    /// strings, multiple values are lists, occurrences are numbers and flags are bools.
+    pub fn to_json(&self) -> String {
+        match &self.kind {
+            ArgKind::String {
+                value: (value, _),
+                default,
+                ..
+            } => match (value.is_empty(), default) {
+                (false, _) => json_string(value),
+                (true, Some(default)) => json_string(default),
+                (true, None) => "null".into(),
+            },
+            ArgKind::MultipleStrings {
+                values, default, ..
+            } => {
+                let values: Vec<String> = if values.is_empty() {
+                    default.iter().map(|v| json_string(v)).collect()
+                } else {
+                    values.iter().map(|(v, _)| json_string(v)).collect()
+                };
+                format!("[{}]", values.join(", "))
+            }
+            ArgKind::Occurences(i) => i.to_string(),
+            ArgKind::Bool(bool) => bool.to_string(),
+            ArgKind::OptionalBool(bool) => bool.map_or("null".into(), |b| b.to_string()),
+        }
+    }
+
+    /// Error when the number of entered values doesn't fit `num_args`.
+    /// Optional args with no values are always allowed.
+    pub fn value_count_error(&self) -> Option<String> {
+        let ArgKind::MultipleStrings {
+            values,
+            num_args: Some(range),
+            ..
+        } = &self.kind
+        else {
+            return None;
+        };
+
+        let localization = &*self.localization;
+        if values.len() < range.min_values() && !(self.optional && values.is_empty()) {
+            let (start, middle, end) = &localization.error_too_few_values;
+            Some(format!(
+                "{start}{}{middle}{}{end}",
+                self.name,
+                range.min_values()
+            ))
+        } else if values.len() > range.max_values() {
+            let (start, middle, end) = &localization.error_too_many_values;
+            Some(format!(
+                "{start}{}{middle}{}{end}",
+                self.name,
+                range.max_values()
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Hint shown in empty text fields. Single values show the default unless
+    /// it's hidden, then "Optional", then the value name. Each row of multiple values shows the
+    /// value name, since the row itself is never optional.
+    pub fn hint_text(&self) -> String {
+        let value_name = self.value_names.join(" ");
+        let optional = &self.localization.optional;
+        match &self.kind {
+            ArgKind::String {
+                default: Some(default),
+                ..
+            } if !self.hide_default => default.clone(),
+            ArgKind::String { .. } if self.optional && !self.forbid_empty => optional.clone(),
+            ArgKind::MultipleStrings { .. } if value_name.is_empty() && !self.forbid_empty => {
+                optional.clone()
+            }
+            _ => value_name,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn ui_single_row(
         ui: &mut Ui,
         (value, id): &mut (String, Uuid),
-        default: &Option<String>,
+        hint_text: &str,
         possible: &[String],
         value_hint: ValueHint,
         optional: bool,
         validation_error: bool,
-        localization: &'s Localization,
+        localization: &Localization,
+        settings: &ArgSettings,
     ) -> Response {
+        let text_id = Id::new(*id);
+        // Only flagged once the field loses focus, so it isn't red while typing
+        let invalid_email = value_hint == ValueHint::EmailAddress
+            && !value.is_empty()
+            && !ui.memory(|m| m.has_focus(text_id))
+            && !is_email(value);
+
+        // A malformed email is only a warning, clap doesn't check it
         let is_error = (!optional && value.is_empty()) || validation_error;
+        let color = match is_error {
+            true => settings.error_color,
+            false => settings.warning_color,
+        };
+        let is_error = is_error || invalid_email;
         if is_error {
-            Klask::set_error_style(ui);
+            Klask::set_error_style(ui, color);
         }
 
         let inner_response = if possible.is_empty() {
@@ -128,13 +594,14 @@ impl<'s> ArgState<'s> {
                     }
                 }
 
-                ui.add(
-                    TextEdit::singleline(value).hint_text(match (default, optional) {
-                        (Some(default), _) => default.as_str(),
-                        (_, true) => localization.optional.as_str(),
-                        (_, false) => "",
-                    }),
-                );
+                if let Some(prefix) = &settings.env_paste {
+                    Self::ui_env_paste(ui, value, text_id, prefix, localization);
+                }
+
+                let text = ui.add(TextEdit::singleline(value).id(text_id).hint_text(hint_text));
+                if invalid_email {
+                    text.on_hover_text(&localization.error_invalid_email);
+                }
 
                 Some(())
             })
@@ -158,12 +625,96 @@ impl<'s> ArgState<'s> {
         inner_response.response
     }
 
+    /// Button with a popup listing the entries of `history`, returns the clicked one.
+    /// Pressing delete while hovering an entry removes it.
+    pub(crate) fn ui_history(
+        ui: &mut Ui,
+        popup_id: Id,
+        history: &mut VecDeque<Vec<String>>,
+        localization: &Localization,
+    ) -> Option<Vec<String>> {
+        if history.is_empty() {
+            return None;
+        }
+
+        let button = ui
+            .small_button("↓")
+            .on_hover_text(&localization.field_history);
+        if button.clicked() {
+            ui.memory_mut(|m| m.toggle_popup(popup_id));
+        }
+
+        let mut picked = None;
+        egui::popup_below_widget(ui, popup_id, &button, |ui| {
+            ui.set_min_width(200.0);
+            let mut remove_index = None;
+            for (index, entry) in history.iter().enumerate() {
+                let label = ui.selectable_label(false, entry.join(", "));
+                if label.clicked() {
+                    picked = Some(entry.clone());
+                }
+                if label.hovered() && ui.input(|i| i.key_pressed(Key::Delete)) {
+                    remove_index = Some(index);
+                }
+            }
+            if let Some(index) = remove_index {
+                history.remove(index);
+            }
+        });
+        picked
+    }
+
+    /// Menu with a searchable list of environment variables, picking one sets `value`.
+    fn ui_env_paste(
+        ui: &mut Ui,
+        value: &mut String,
+        text_id: Id,
+        prefix: &str,
+        localization: &Localization,
+    ) {
+        ui.menu_button(&localization.env_paste, |ui| {
+            let search_id = text_id.with("env_paste");
+            let mut search: String = ui.data_mut(|d| d.get_temp(search_id)).unwrap_or_default();
+            ui.text_edit_singleline(&mut search).request_focus();
+
+            let query = search.to_lowercase();
+            let mut vars: Vec<(String, String)> = std::env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .filter(|(key, _)| key.starts_with(prefix) && key.to_lowercase().contains(&query))
+                .collect();
+            vars.sort();
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for (key, env_value) in vars {
+                        if ui.button(key).clicked() {
+                            *value = env_value;
+                            search.clear();
+                            ui.close_menu();
+                        }
+                    }
+                });
+
+            ui.data_mut(|d| d.insert_temp(search_id, search));
+        });
+    }
+
     pub fn get_cmd_args(&self, mut args: Vec<String>) -> Result<Vec<String>, String> {
         match &self.kind {
             ArgKind::String {
-                value: (value, _), ..
+                value: (value, _),
+                default,
+                ..
             } => {
-                if !value.is_empty() {
+                let value = match default {
+                    Some(default) if value.is_empty() && self.expanded_default => Some(default),
+                    _ => Some(value).filter(|v| !v.is_empty()),
+                };
+
+                if let Some(value) = value {
                     if let Some(call_name) = self.call_name.as_ref() {
                         if self.use_equals {
                             args.push(format!("{call_name}={value}"));
@@ -182,31 +733,55 @@ impl<'s> ArgState<'s> {
                     ));
                 }
             }
-            ArgKind::MultipleStrings { values, .. } => {
+            ArgKind::MultipleStrings {
+                values, default, ..
+            } => {
+                if let Some(err) = self.value_count_error() {
+                    return Err(err);
+                }
+
+                let values: Vec<&String> = if values.is_empty() && self.expanded_default {
+                    default.iter().collect()
+                } else {
+                    values.iter().map(|(v, _)| v).collect()
+                };
+
                 if !values.is_empty() {
-                    if let Some(call_name) = &self.call_name {
+                    if let Some(terminator) = &self.value_terminator {
+                        // All values go after a single flag, otherwise the
+                        // terminator would only end the last occurrence
+                        args.extend(self.call_name.clone());
+                        args.extend(values.into_iter().cloned());
+                        args.push(terminator.clone());
+                    } else if let Some(call_name) = &self.call_name {
                         match self.use_equals {
                             true => values
                                 .iter()
-                                .for_each(|v| args.push(format!("{}={}", call_name, v.0))),
+                                .for_each(|v| args.push(format!("{call_name}={v}"))),
                             false => values.iter().for_each(|v| {
-                                args.extend_from_slice(&[call_name.clone(), v.0.clone()])
+                                args.extend_from_slice(&[call_name.clone(), v.to_string()])
                             }),
                         }
                     } else {
                         for value in values {
-                            args.push(value.0.clone());
+                            args.push(value.clone());
                         }
                     }
                 }
             }
             &ArgKind::Occurences(i) => {
-                for _ in 0..i {
-                    args.push(
-                        self.call_name
-                            .clone()
-                            .ok_or_else(|| "Internal error.".to_string())?,
-                    );
+                let call_name = self
+                    .call_name
+                    .as_ref()
+                    .ok_or_else(|| "Internal error.".to_string())?;
+                let is_short = !call_name.starts_with("--");
+
+                if self.settings.combine_short_flags && is_short && i > 0 {
+                    args.push(format!("-{}", call_name[1..].repeat(i.into())));
+                } else {
+                    for _ in 0..i {
+                        args.push(call_name.clone());
+                    }
                 }
             }
             &ArgKind::Bool(bool) => {
@@ -218,122 +793,411 @@ impl<'s> ArgState<'s> {
                     );
                 }
             }
+            ArgKind::OptionalBool(bool) => {
+                if let Some(value) = bool {
+                    match &self.call_name {
+                        Some(call_name) if self.use_equals => {
+                            args.push(format!("{call_name}={value}"))
+                        }
+                        Some(call_name) => {
+                            args.extend_from_slice(&[call_name.clone(), value.to_string()])
+                        }
+                        None => args.push(value.to_string()),
+                    }
+                }
+            }
         }
 
         Ok(args)
     }
 }
 
-impl Widget for &mut ArgState<'_> {
+impl Widget for &mut ArgState {
     fn ui(self, ui: &mut Ui) -> eframe::egui::Response {
-        let localization = self.localization;
-        let label = ui.label(&self.name);
+        let localization = &*self.localization;
+        let label = ui
+            .horizontal(|ui| {
+                // Empty lists have no text field to outline, so required ones are marked here
+                let label = match self.kind {
+                    ArgKind::MultipleStrings { .. } if !self.optional => {
+                        ui.label(format!("{} *", self.name))
+                    }
+                    _ => ui.label(&self.name),
+                };
+                if let Some(env) = &self.env {
+                    if self.is_from_env() {
+                        ui.colored_label(Color32::LIGHT_BLUE, format!("${env}"))
+                            .on_hover_text(format!("{}{env}", localization.env_var_value_from));
+                    } else {
+                        ui.weak(format!("${env}"))
+                            .on_hover_text(format!("{}{env}", localization.env_var_sets_arg));
+                    }
+                }
+                label
+            })
+            .inner;
+
+        if self.scroll_to {
+            self.scroll_to = false;
+            label.scroll_to_me(Some(Align::Center));
+            if let ArgKind::String { value: (_, id), .. } = &self.kind {
+                ui.memory_mut(|m| m.request_focus(Id::new(*id)));
+            }
+        }
 
+        let label_rect = label.rect;
         if let Some(desc) = &self.desc {
             label.on_hover_text(desc);
         }
 
         // Grid column automatically switches here
 
+        let response = if self.unresolved_template_vars.is_empty() {
+            self.ui_value(ui)
+        } else {
+            let warning = format!(
+                "{}{}",
+                localization.warning_unresolved_template,
+                self.unresolved_template_vars.join(", ")
+            );
+            Frame::none()
+                .stroke(Stroke::new(1.0, self.settings.warning_color))
+                .inner_margin(2.0)
+                .show(ui, |ui| self.ui_value(ui))
+                .response
+                .on_hover_text(warning)
+        };
+
+        let text_focused = self
+            .text_ids()
+            .into_iter()
+            .any(|id| ui.memory(|m| m.has_focus(Id::new(id))));
+        let clicked = ui.input(|i| i.pointer.any_pressed())
+            && ui.rect_contains_pointer(label_rect.union(response.rect));
+        if text_focused || clicked {
+            self.focused_at = Some(ui.input(|i| i.time));
+        }
+
+        response
+    }
+}
+
+impl ArgState {
+    /// Ids of the text fields, which are based on the ids of the values
+    fn text_ids(&self) -> Vec<Uuid> {
+        match &self.kind {
+            ArgKind::String { value: (_, id), .. } => vec![*id],
+            ArgKind::MultipleStrings { values, .. } => values.iter().map(|(_, id)| *id).collect(),
+            _ => vec![],
+        }
+    }
+
+    fn ui_value(&mut self, ui: &mut Ui) -> Response {
+        let localization = &*self.localization;
         let is_validation_error = self.validation_error.is_some();
+        let settings = &self.settings;
+        let inline_errors = settings.inline_validation_errors;
+        let error_color = settings.error_color;
+        // Without the file and directory buttons paths are typed like any other value
+        let disable_file_dialogs = self.settings.disable_file_dialogs;
+        let dialog_hint = |hint: ValueHint| match disable_file_dialogs {
+            true => ValueHint::Unknown,
+            false => hint,
+        };
 
-        match &mut self.kind {
-            ArgKind::String {
-                value,
-                default,
-                possible,
-                value_hint,
-            } => ArgState::ui_single_row(
-                ui,
-                value,
-                default,
-                possible,
-                *value_hint,
-                self.optional && !self.forbid_empty,
-                is_validation_error,
-                localization,
-            ),
-            ArgKind::MultipleStrings {
-                values,
-                default,
-                possible,
-                value_hint,
-                ..
-            } => {
-                let forbid_empty = self.forbid_empty;
-                let mut list = ui
-                    .vertical(|ui| {
-                        let mut remove_index = None;
+        let hint_text = self.hint_text();
+        let history_id = ui.make_persistent_id(&self.id).with("history");
+        let history = &mut self.history;
+
+        let enabled = self.enabled && self.conflicts_with_set.is_none();
+        let response = ui
+            .add_enabled_ui(enabled, |ui| {
+                ui.vertical(|ui| {
+                    let response = match &mut self.kind {
+                        ArgKind::String {
+                            value,
+                            possible,
+                            value_hint,
+                            ..
+                        } => {
+                            let previous = value.0.clone();
+                            let optional = self.optional && !self.forbid_empty;
+                            let response = ui
+                                .horizontal(|ui| {
+                                    let response = ArgState::ui_single_row(
+                                        ui,
+                                        value,
+                                        &hint_text,
+                                        possible,
+                                        dialog_hint(*value_hint),
+                                        optional,
+                                        is_validation_error,
+                                        localization,
+                                        settings,
+                                    );
+                                    let picked =
+                                        Self::ui_history(ui, history_id, history, localization);
+                                    if let Some(entry) = picked {
+                                        value.0 = entry.concat();
+                                    }
+                                    response
+                                })
+                                .inner;
+                            // The error is probably fixed, the next run checks again
+                            if value.0 != previous {
+                                self.validation_error = None;
+                            }
+
+                            match &self.validation_error {
+                                Some(message) if !inline_errors => response.on_hover_text(message),
+                                _ => response,
+                            }
+                        }
+                        ArgKind::MultipleStrings {
+                            values,
+                            default,
+                            possible,
+                            value_hint,
+                            num_args,
+                        } => {
+                            let forbid_empty = self.forbid_empty;
+                            let (min_values, max_values) = num_args
+                                .map_or((0, usize::MAX), |r| (r.min_values(), r.max_values()));
+                            let previous: Vec<String> =
+                                values.iter().map(|(v, _)| v.clone()).collect();
+                            let mut list = ui
+                                .vertical(|ui| {
+                                    let mut remove_index = None;
+
+                                    for (index, value) in values.iter_mut().enumerate() {
+                                        ui.horizontal(|ui| {
+                                            if ui.small_button("-").clicked() {
+                                                remove_index = Some(index);
+                                            }
+
+                                            ArgState::ui_single_row(
+                                                ui,
+                                                value,
+                                                &hint_text,
+                                                possible,
+                                                dialog_hint(*value_hint),
+                                                !forbid_empty,
+                                                is_validation_error,
+                                                localization,
+                                                settings,
+                                            );
+                                        });
+                                    }
+
+                                    if let Some(index) = remove_index {
+                                        values.remove(index);
+                                    }
 
-                        for (index, value) in values.iter_mut().enumerate() {
+                                    ui.horizontal(|ui| {
+                                        let too_few =
+                                            !values.is_empty() && values.len() < min_values;
+                                        if too_few {
+                                            Klask::set_error_style(ui, error_color);
+                                        }
+
+                                        if ui
+                                            .add_enabled(
+                                                values.len() < max_values,
+                                                Button::new(&localization.new_value),
+                                            )
+                                            .clicked()
+                                        {
+                                            values.push((String::new(), Uuid::new_v4()));
+                                        }
+
+                                        if too_few {
+                                            ui.reset_style();
+                                        }
+
+                                        let text = if default.is_empty() {
+                                            &localization.reset
+                                        } else {
+                                            &localization.reset_to_default
+                                        };
+
+                                        ui.add_space(20.0);
+                                        if ui.button(text).clicked() {
+                                            *values = default
+                                                .iter()
+                                                .map(|s| (s.to_string(), Uuid::new_v4()))
+                                                .collect();
+                                        }
+
+                                        let picked =
+                                            Self::ui_history(ui, history_id, history, localization);
+                                        if let Some(entry) = picked {
+                                            *values = entry
+                                                .into_iter()
+                                                .map(|s| (s, Uuid::new_v4()))
+                                                .collect();
+                                        }
+                                    });
+                                })
+                                .response;
+
+                            if !self.optional && values.is_empty() {
+                                ui.painter().rect_stroke(
+                                    list.rect.expand(2.0),
+                                    2.0,
+                                    Stroke::new(1.0, error_color),
+                                );
+                            }
+
+                            // The list response doesn't know about edits in its rows
+                            if !values.iter().map(|(v, _)| v).eq(previous.iter()) {
+                                self.validation_error = None;
+                            }
+                            if let Some(message) = &self.validation_error {
+                                if !inline_errors {
+                                    list = list.on_hover_text(message);
+                                }
+                            }
+
+                            list
+                        }
+                        ArgKind::Occurences(i) => {
                             ui.horizontal(|ui| {
                                 if ui.small_button("-").clicked() {
-                                    remove_index = Some(index);
+                                    *i = i.saturating_sub(1);
                                 }
 
-                                ArgState::ui_single_row(
-                                    ui,
-                                    value,
-                                    &None,
-                                    possible,
-                                    *value_hint,
-                                    !forbid_empty,
-                                    is_validation_error,
-                                    localization,
-                                );
-                            });
-                        }
+                                ui.label(i.to_string());
 
-                        if let Some(index) = remove_index {
-                            values.remove(index);
+                                if ui.small_button("+").clicked() {
+                                    *i += 1;
+                                }
+                            })
+                            .response
                         }
-
-                        ui.horizontal(|ui| {
-                            if ui.button(&localization.new_value).clicked() {
-                                values.push((String::new(), Uuid::new_v4()));
+                        ArgKind::Bool(bool) => ui.checkbox(bool, ""),
+                        ArgKind::OptionalBool(bool) => {
+                            let mut checked = bool.unwrap_or(false);
+                            let response = ui
+                                .add(Checkbox::new(&mut checked, "").indeterminate(bool.is_none()))
+                                .on_hover_text(&localization.optional_bool_hint);
+                            if response.clicked() {
+                                *bool = match bool {
+                                    None => Some(true),
+                                    Some(true) => Some(false),
+                                    Some(false) => None,
+                                };
                             }
+                            response
+                        }
+                    };
 
-                            let text = if default.is_empty() {
-                                &localization.reset
-                            } else {
-                                &localization.reset_to_default
-                            };
-
-                            ui.add_space(20.0);
-                            if ui.button(text).clicked() {
-                                *values = default
-                                    .iter()
-                                    .map(|s| (s.to_string(), Uuid::new_v4()))
-                                    .collect();
-                            }
-                        });
-                    })
-                    .response;
-
-                if let Some(message) = &self.validation_error {
-                    list = list.on_hover_text(message);
-                    if list.changed() {
-                        self.validation_error = None;
+                    if let Some(message) = &self.validation_error {
+                        if inline_errors {
+                            ui.colored_label(error_color, message);
+                        }
                     }
-                }
 
-                list
-            }
-            ArgKind::Occurences(i) => {
-                ui.horizontal(|ui| {
-                    if ui.small_button("-").clicked() {
-                        *i = (*i - 1).max(0);
+                    if let Some(help) = &self.inline_help {
+                        ui.weak(help);
                     }
 
-                    ui.label(i.to_string());
-
-                    if ui.small_button("+").clicked() {
-                        *i += 1;
-                    }
+                    response
                 })
-                .response
+                .inner
+            })
+            .inner;
+
+        match &self.conflicts_with_set {
+            Some(other) => {
+                let (start, end) = &localization.disabled_by_conflict;
+                response.on_disabled_hover_text(format!("{start}{other}{end}"))
             }
-            ArgKind::Bool(bool) => ui.checkbox(bool, ""),
+            None => response,
+        }
+    }
+}
+
+/// Quotes and escapes a string for JSON
+pub fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
         }
     }
+    json.push('"');
+    json
+}
+
+/// Loose check for `local@domain.tld`, anything stricter rejects valid addresses
+fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && !value.contains(char::is_whitespace)
+                && domain
+                    .split_once('.')
+                    .is_some_and(|(name, _)| !name.is_empty() && !domain.ends_with('.'))
+        }
+        None => false,
+    }
+}
+
+/// Same values as clap's `FalseyValueParser`
+fn is_truthy(value: &str) -> bool {
+    !matches!(
+        value.to_lowercase().as_str(),
+        "" | "n" | "no" | "f" | "false" | "off" | "0"
+    )
+}
+
+/// Guesses a path hint from an arg id like `config_file`, see
+/// [`crate::Settings::infer_path_from_name`].
+fn infer_value_hint(id: &str) -> ValueHint {
+    if id.ends_with("_file") {
+        ValueHint::FilePath
+    } else if id.ends_with("_dir") {
+        ValueHint::DirPath
+    } else if id.ends_with("_path") {
+        ValueHint::AnyPath
+    } else {
+        ValueHint::Unknown
+    }
+}
+
+/// Replaces `{{KEY}}` placeholders, see [`ArgState::new`].
+fn expand_template(
+    value: &str,
+    vars: Option<&HashMap<String, String>>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let key = rest[start + 2..start + 2 + len].trim();
+
+        expanded.push_str(&rest[..start]);
+        match vars
+            .and_then(|vars| vars.get(key).cloned())
+            .or_else(|| std::env::var(key).ok())
+        {
+            Some(value) => expanded.push_str(&value),
+            None => unresolved.push(key.to_string()),
+        }
+        rest = &rest[start + len + 4..];
+    }
+
+    expanded.push_str(rest);
+    expanded
 }
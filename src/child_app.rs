@@ -1,19 +1,69 @@
-use crate::{ExecutionError, CHILD_APP_ENV_VAR};
+use crate::ExecutionError;
+use clap::ArgMatches;
 use eframe::egui;
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Read, Write},
-    path::PathBuf,
-    process::{Child, Command, Stdio},
-    sync::mpsc::{self, Receiver},
-    thread,
+    fmt::{self, Debug},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
 };
 
+/// Number of temporary output files created so far, for unique names
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Debug)]
 pub struct ChildApp {
-    child: Child,
-    stdout: Option<Receiver<Option<String>>>,
-    stderr: Option<Receiver<Option<String>>>,
+    process: Process,
+    /// Lines are sent as bytes, as the output doesn't have to be valid UTF-8
+    stdout: Option<Receiver<Option<Vec<u8>>>>,
+    stderr: Option<Receiver<Option<Vec<u8>>>>,
+    /// Error returned by a closure running in process
+    error: Option<String>,
+    /// Started with `detachable`, see [`ChildApp::run`]
+    detachable: bool,
+    /// Temporary output file that couldn't be removed while it was open, like on windows
+    temp_file: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+enum Process {
+    /// Shared with the thread reading the output file of a detachable child
+    Child(Arc<Mutex<Child>>),
+    /// The handle is taken once the thread is finished
+    Thread(Option<JoinHandle<Result<(), String>>>, Option<ExitStatus>),
+    /// Child that is no longer tracked and won't be killed on drop
+    Detached,
+}
+
+/// Closure called on a thread instead of running the binary in a subprocess
+#[derive(Clone)]
+pub struct InProcess(pub Arc<InProcessFn>);
+
+/// The error is already formatted, so the closure doesn't need to be generic
+type InProcessFn = dyn Fn(&ArgMatches) -> Result<(), String> + Send + Sync;
+
+impl InProcess {
+    pub fn new<E: fmt::Display>(
+        f: impl Fn(&ArgMatches) -> Result<(), E> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(move |matches| {
+            f(matches).map_err(|err| err.to_string())
+        }))
+    }
+}
+
+impl Debug for InProcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("InProcess")
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -23,25 +73,53 @@ pub enum StdinType {
 }
 
 impl ChildApp {
+    /// `step` is the index into [`crate::Settings::run_sequence`] plus one, 0 for the main command.
+    /// `env` has to set [`crate::Settings::child_env_var`] to the step.
+    /// A `detachable` child gets its own session on unix and process group on windows,
+    /// so it isn't stopped with the terminal the GUI was started from. Instead of pipes
+    /// it writes its output to `log_file` or an already removed temporary file, which is
+    /// read back, so it can keep printing after the GUI exited. The `note` of the run is
+    /// written to `log_file` before the output, see [`crate::output::note_line`].
+    /// With a `shell_suffix` the quoted args and the suffix are passed to a shell, see
+    /// [`crate::Settings::shell_suffix`].
+    #[allow(clippy::too_many_arguments)]
     pub fn run(
         args: Vec<String>,
-        env: Option<Vec<(String, String)>>,
+        step: usize,
+        env: Vec<(String, String)>,
         stdin: Option<StdinType>,
         working_dir: Option<String>,
+        detachable: bool,
+        log_file: Option<&Path>,
+        note: Option<&str>,
+        shell_suffix: Option<&str>,
         ctx: egui::Context,
     ) -> Result<Self, ExecutionError> {
-        let mut child = Command::new(std::env::current_exe()?);
+        log!(debug, "Spawning step {step} with args {args:?}");
+        let exe = std::env::current_exe()?;
+        let mut child = match shell_suffix {
+            Some(suffix) => shell_command(&shell_line(&exe.to_string_lossy(), &args, suffix)),
+            None => {
+                let mut child = Command::new(exe);
+                child.args(args);
+                child
+            }
+        };
 
-        child
-            .env(CHILD_APP_ENV_VAR, "")
-            .args(args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+        child.stdin(Stdio::piped());
+        let (output_file, temp_file) = match detachable {
+            true => {
+                let (writer, reader, temp_file) = output_files(log_file, note)?;
+                child.stdout(writer.try_clone()?).stderr(writer);
+                (Some(reader), temp_file)
+            }
+            false => {
+                child.stdout(Stdio::piped()).stderr(Stdio::piped());
+                (None, None)
+            }
+        };
 
-        if let Some(env) = env {
-            child.envs(env);
-        }
+        child.envs(env);
 
         if let Some(working_dir) = working_dir {
             if !working_dir.is_empty() {
@@ -49,26 +127,36 @@ impl ChildApp {
             }
         }
 
+        if detachable {
+            new_session(&mut child);
+        }
+
         let mut child = child.spawn()?;
+        log!(debug, "Spawned child with PID {}", child.id());
+        let child_stdin = child.stdin.take();
+        let child = Arc::new(Mutex::new(child));
 
-        let stdout = Self::spawn_thread_reader(
-            child
-                .stdout
-                .take()
-                .ok_or(ExecutionError::NoStdoutOrStderr)?,
-            ctx.clone(),
-        );
-
-        let stderr = Self::spawn_thread_reader(
-            child
-                .stderr
-                .take()
-                .ok_or(ExecutionError::NoStdoutOrStderr)?,
-            ctx,
-        );
+        let (stdout, stderr) = match output_file {
+            Some(file) => (Self::spawn_file_reader(file, &child, ctx), None),
+            None => {
+                let mut child = child.lock().unwrap();
+                let stdout = child
+                    .stdout
+                    .take()
+                    .ok_or(ExecutionError::NoStdoutOrStderr)?;
+                let stderr = child
+                    .stderr
+                    .take()
+                    .ok_or(ExecutionError::NoStdoutOrStderr)?;
+                (
+                    Self::spawn_thread_reader(stdout, ctx.clone()),
+                    Some(Self::spawn_thread_reader(stderr, ctx)),
+                )
+            }
+        };
 
         if let Some(stdin) = stdin {
-            let mut child_stdin = child.stdin.take().unwrap();
+            let mut child_stdin = child_stdin.unwrap();
             match stdin {
                 StdinType::Text(text) => {
                     child_stdin.write_all(text.as_bytes())?;
@@ -81,14 +169,64 @@ impl ChildApp {
         }
 
         Ok(Self {
-            child,
+            process: Process::Child(child),
             stdout: Some(stdout),
-            stderr: Some(stderr),
+            stderr,
+            error: None,
+            detachable,
+            temp_file,
         })
     }
 
-    pub fn read(&mut self) -> String {
-        let mut out = String::new();
+    /// Calls the closure on a new thread. On unix stdout and stderr of the whole
+    /// process are redirected to the output while it runs.
+    pub fn run_in_process(
+        f: InProcess,
+        matches: ArgMatches,
+        ctx: egui::Context,
+    ) -> Result<Self, ExecutionError> {
+        log!(debug, "Running the closure in process");
+        // Dropping the sender at the end of the thread finishes the output
+        let (sender, finished) = mpsc::channel();
+
+        #[cfg(unix)]
+        let (redirect, stdout, stderr) = {
+            let (redirect, stdout, stderr) = redirect::Redirect::new()?;
+            (
+                redirect,
+                Self::spawn_thread_reader(stdout, ctx.clone()),
+                Some(Self::spawn_thread_reader(stderr, ctx.clone())),
+            )
+        };
+        #[cfg(not(unix))]
+        let (stdout, stderr) = (finished, None);
+
+        let handle = thread::spawn(move || {
+            #[cfg(unix)]
+            let _redirect = redirect;
+            let _sender: mpsc::Sender<Option<Vec<u8>>> = sender;
+            let result = (f.0)(&matches);
+            ctx.request_repaint();
+            result
+        });
+
+        #[cfg(unix)]
+        drop(finished);
+
+        Ok(Self {
+            process: Process::Thread(Some(handle), None),
+            stdout: Some(stdout),
+            stderr,
+            error: None,
+            detachable: false,
+            temp_file: None,
+        })
+    }
+
+    /// New output since the last read, stdout before stderr. It's split into whole lines,
+    /// see [`crate::output::decode_output`].
+    pub fn read(&mut self) -> Vec<u8> {
+        let mut out = vec![];
         Self::read_stdio(&mut out, &mut self.stdout);
         Self::read_stdio(&mut out, &mut self.stderr);
         out
@@ -98,25 +236,113 @@ impl ChildApp {
         self.stdout.is_some() || self.stderr.is_some()
     }
 
-    pub fn kill(&mut self) {
-        drop(self.child.kill());
+    /// Returns the exit status once the child has exited.
+    /// A closure running in process exits with 101 if it panicked, like a Rust binary,
+    /// and with 1 if it returned an error.
+    pub fn exit_status(&mut self) -> Option<ExitStatus> {
+        match &mut self.process {
+            Process::Child(child) => child.lock().unwrap().try_wait().ok().flatten(),
+            Process::Detached => None,
+            Process::Thread(handle, status) => {
+                if let Some(handle) = handle.take_if(|h| h.is_finished()) {
+                    let code = match handle.join() {
+                        Ok(Ok(())) => 0,
+                        Ok(Err(err)) => {
+                            self.error = Some(err);
+                            1
+                        }
+                        Err(_) => 101,
+                    };
+                    *status = Some(thread_exit_status(code));
+                }
+                *status
+            }
+        }
+    }
+
+    /// Error returned by a closure running in process, once it's finished
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Process id of the child, None for threads
+    pub fn pid(&self) -> Option<u32> {
+        match &self.process {
+            Process::Child(child) => Some(child.lock().unwrap().id()),
+            _ => None,
+        }
+    }
+
+    /// Sends a signal like `SIGINT` to the child process. Does nothing for threads.
+    #[cfg(unix)]
+    pub fn send_signal(&self, signal: libc::c_int) -> std::io::Result<()> {
+        if let Process::Child(child) = &self.process {
+            // Holding the lock, so the child isn't reaped in the meantime
+            let child = child.lock().unwrap();
+            // SAFETY: kill doesn't touch memory, the child isn't reaped until try_wait
+            if unsafe { libc::kill(child.id() as libc::pid_t, signal) } == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Threads can't be killed
+    pub fn can_kill(&self) -> bool {
+        matches!(self.process, Process::Child(_))
+    }
+
+    /// Whether the child can keep running after the GUI exited, see [`ChildApp::run`]
+    pub fn is_detachable(&self) -> bool {
+        self.detachable
+    }
+
+    /// Stops reading the output and lets a `detachable` child process keep running after
+    /// this is dropped. Other children would fail printing to the closed pipes, so they're
+    /// left alone and killed on drop.
+    pub fn detach(&mut self) {
+        if !self.detachable {
+            return;
+        }
+        if let Process::Child(_) = self.process {
+            self.process = Process::Detached;
+        }
         self.stdout = None;
         self.stderr = None;
     }
 
+    pub fn kill(&mut self) {
+        if let Process::Child(child) = &mut self.process {
+            drop(child.lock().unwrap().kill());
+            self.stdout = None;
+            self.stderr = None;
+        }
+    }
+
     fn spawn_thread_reader<R: Read + Send + Sync + 'static>(
         stdio: R,
         ctx: egui::Context,
-    ) -> Receiver<Option<String>> {
+    ) -> Receiver<Option<Vec<u8>>> {
         let mut reader = BufReader::new(stdio);
         let (tx, rx) = mpsc::channel();
         thread::spawn(move || loop {
-            let mut output = String::new();
-            if let Ok(0) = reader.read_line(&mut output) {
-                // End of output
-                drop(tx.send(None));
-                ctx.request_repaint();
-                break;
+            let mut output = vec![];
+            match reader.read_until(b'\n', &mut output) {
+                Ok(0) => {
+                    // End of output
+                    drop(tx.send(None));
+                    ctx.request_repaint();
+                    break;
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    // Reading again would most likely fail the same way
+                    log!(warn, "Couldn't read output of the child: {err}");
+                    drop(tx.send(Some(output)));
+                    drop(tx.send(None));
+                    ctx.request_repaint();
+                    break;
+                }
             }
             // Send returns error only if data will never be received
             if tx.send(Some(output)).is_err() {
@@ -127,22 +353,325 @@ impl ChildApp {
         rx
     }
 
-    fn read_stdio(output: &mut String, stdio: &mut Option<Receiver<Option<String>>>) {
+    /// Like [`ChildApp::spawn_thread_reader`], for an output file that only ends once the
+    /// child exited
+    fn spawn_file_reader(
+        file: File,
+        child: &Arc<Mutex<Child>>,
+        ctx: egui::Context,
+    ) -> Receiver<Option<Vec<u8>>> {
+        let mut reader = BufReader::new(file);
+        let child = Arc::clone(child);
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut line = vec![];
+            let mut exited = false;
+            loop {
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(_) if line.ends_with(b"\n") => {
+                        if tx.send(Some(std::mem::take(&mut line))).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                    // Read once more after the exit, for what was printed right before
+                    Ok(_) if !exited => {
+                        exited = !matches!(child.lock().unwrap().try_wait(), Ok(None));
+                        if !exited {
+                            thread::sleep(Duration::from_millis(50));
+                        }
+                    }
+                    result => {
+                        if let Err(err) = result {
+                            log!(warn, "Couldn't read output of the child: {err}");
+                        }
+                        if !line.is_empty() {
+                            drop(tx.send(Some(line)));
+                        }
+                        drop(tx.send(None));
+                        ctx.request_repaint();
+                        break;
+                    }
+                }
+            }
+        });
+        rx
+    }
+
+    fn read_stdio(output: &mut Vec<u8>, stdio: &mut Option<Receiver<Option<Vec<u8>>>>) {
         if let Some(receiver) = stdio {
-            for line in receiver.try_iter() {
-                if let Some(line) = line {
-                    output.push_str(&line);
-                } else {
-                    *stdio = None;
-                    return;
+            loop {
+                match receiver.try_recv() {
+                    Ok(Some(line)) => output.extend(line),
+                    Ok(None) | Err(TryRecvError::Disconnected) => {
+                        *stdio = None;
+                        return;
+                    }
+                    Err(TryRecvError::Empty) => return,
                 }
             }
         }
     }
 }
 
+/// Files a detachable child writes its output to and it's read back from: `log_file`
+/// from its current end after the `note`, or a new temporary file. The path of the
+/// temporary file is returned if it couldn't be removed yet.
+fn output_files(
+    log_file: Option<&Path>,
+    note: Option<&str>,
+) -> std::io::Result<(File, File, Option<PathBuf>)> {
+    if let Some(path) = log_file {
+        let mut writer = OpenOptions::new().create(true).append(true).open(path)?;
+        if let Some(note) = note {
+            writer.write_all(crate::output::note_line(note).as_bytes())?;
+        }
+        let mut reader = File::open(path)?;
+        reader.seek(SeekFrom::End(0))?;
+        return Ok((writer, reader, None));
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "klask-{}-{}.log",
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let writer = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    let reader = File::open(&path)?;
+    // The open files stay usable, and nothing is left behind once they're closed
+    match fs::remove_file(&path) {
+        Ok(()) => Ok((writer, reader, None)),
+        Err(err) => {
+            log!(debug, "Removing {} after the run: {err}", path.display());
+            Ok((writer, reader, Some(path)))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn new_session(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: setsid is async-signal-safe and doesn't allocate
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn new_session(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
+/// Line passed to the shell: `exe` and `args` quoted with [`sh_quote`] ([`cmd_quote`] on
+/// windows), followed by the unquoted `suffix`
+pub fn shell_line(exe: &str, args: &[String], suffix: &str) -> String {
+    #[cfg(unix)]
+    let quote = sh_quote;
+    #[cfg(windows)]
+    let quote = cmd_quote;
+    let mut line: Vec<_> = std::iter::once(exe)
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect();
+    if !suffix.is_empty() {
+        line.push(suffix.to_string());
+    }
+    line.join(" ")
+}
+
+/// How the shell is started with `line`, as it could be typed into a terminal
+#[cfg(unix)]
+pub fn shell_preview(line: &str) -> String {
+    format!("sh -c {}", sh_quote(line))
+}
+
+/// How the shell is started with `line`, as it could be typed into a terminal
+#[cfg(windows)]
+pub fn shell_preview(line: &str) -> String {
+    format!("cmd /C {line}")
+}
+
+/// Command running `line` in `sh -c`
+#[cfg(unix)]
+fn shell_command(line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(line);
+    command
+}
+
+/// Command running `line` in `cmd /C`
+#[cfg(windows)]
+fn shell_command(line: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut command = Command::new("cmd");
+    command.arg("/C").raw_arg(line);
+    command
+}
+
+/// Single quotes `arg`, so `sh` passes it on as it is
+#[cfg(unix)]
+fn sh_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Double quotes `arg` for the command line of a program like [`Command::arg`], then
+/// escapes every character `cmd` interprets with `^`, also the quotes. That way the
+/// line doesn't start with a quote, which `cmd /C` would remove.
+#[cfg(any(windows, test))]
+fn cmd_quote(arg: &str) -> String {
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            // Backslashes only escape before a quote
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes + 1));
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+        quoted.push(c);
+    }
+    quoted.push_str(&"\\".repeat(backslashes));
+    quoted.push('"');
+
+    let mut escaped = String::with_capacity(quoted.len());
+    for c in quoted.chars() {
+        if "()%!^\"<>&|".contains(c) {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(unix)]
+fn thread_exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn thread_exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
 impl Drop for ChildApp {
     fn drop(&mut self) {
         self.kill();
+        // A detached child still writes to it
+        if let Some(path) = self.temp_file.take() {
+            if !matches!(self.process, Process::Detached) {
+                drop(fs::remove_file(path));
+            }
+        }
     }
 }
+
+#[cfg(unix)]
+mod redirect {
+    use std::{
+        fs::File,
+        io::{self, Write},
+        os::unix::io::{FromRawFd, RawFd},
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    /// Set while a [`Redirect`] exists, nested ones would restore the fds out of order
+    /// and mix the output of both closures
+    static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+    /// Points stdout and stderr of the whole process into pipes until dropped.
+    /// Only one can exist at a time, also across several GUIs in one process.
+    pub struct Redirect {
+        saved: [RawFd; 2],
+    }
+
+    impl Redirect {
+        /// Returns the reading ends of the stdout and stderr pipes.
+        /// Fails while another closure's output is redirected.
+        pub fn new() -> io::Result<(Self, File, File)> {
+            if ACTIVE.swap(true, Ordering::AcqRel) {
+                return Err(io::Error::other(
+                    "another closure is already running in this process",
+                ));
+            }
+
+            let redirected = redirect(libc::STDOUT_FILENO).and_then(|(saved_stdout, stdout)| {
+                redirect(libc::STDERR_FILENO)
+                    .map(|(saved_stderr, stderr)| (saved_stdout, stdout, saved_stderr, stderr))
+                    .inspect_err(|_| restore(libc::STDOUT_FILENO, saved_stdout))
+            });
+            let (saved_stdout, stdout, saved_stderr, stderr) =
+                redirected.inspect_err(|_| ACTIVE.store(false, Ordering::Release))?;
+
+            Ok((
+                Self {
+                    saved: [saved_stdout, saved_stderr],
+                },
+                stdout,
+                stderr,
+            ))
+        }
+    }
+
+    impl Drop for Redirect {
+        fn drop(&mut self) {
+            drop(io::stdout().flush());
+            drop(io::stderr().flush());
+            restore(libc::STDOUT_FILENO, self.saved[0]);
+            restore(libc::STDERR_FILENO, self.saved[1]);
+            ACTIVE.store(false, Ordering::Release);
+        }
+    }
+
+    /// Returns a copy of the original fd and the reading end of the pipe
+    fn redirect(fd: RawFd) -> io::Result<(RawFd, File)> {
+        let mut pipe = [0; 2];
+        // SAFETY: all fds are checked for errors and owned by this function
+        unsafe {
+            if libc::pipe(pipe.as_mut_ptr()) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            let [read, write] = pipe;
+
+            let saved = libc::dup(fd);
+            if saved == -1 || libc::dup2(write, fd) == -1 {
+                let err = io::Error::last_os_error();
+                libc::close(read);
+                libc::close(write);
+                if saved != -1 {
+                    libc::close(saved);
+                }
+                return Err(err);
+            }
+
+            // Now only `fd` points to the writing end
+            libc::close(write);
+            Ok((saved, File::from_raw_fd(read)))
+        }
+    }
+
+    fn restore(fd: RawFd, saved: RawFd) {
+        // SAFETY: `saved` was created by `redirect` and isn't used afterwards
+        unsafe {
+            libc::dup2(saved, fd);
+            libc::close(saved);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,122 @@
+#[cfg(unix)]
+#[test]
+fn read_output_file() {
+    use super::{output_files, ChildApp};
+    use eframe::egui;
+    use std::{
+        process::Command,
+        sync::{Arc, Mutex},
+    };
+
+    let (writer, reader, _) = output_files(None, None).unwrap();
+    let child = Command::new("sh")
+        .args(["-c", "echo one; sleep 0.2; printf two"])
+        .stdout(writer)
+        .spawn()
+        .unwrap();
+    let child = Arc::new(Mutex::new(child));
+
+    // The reader waits for the line printed after the pause
+    let output = ChildApp::spawn_file_reader(reader, &child, egui::Context::default());
+    let lines: Vec<_> = output.iter().map_while(|line| line).collect();
+    assert_eq!(lines, [b"one\n".to_vec(), b"two".to_vec()]);
+}
+
+#[test]
+fn note_in_log_file() {
+    use super::output_files;
+    use std::{
+        fs,
+        io::{Read, Write},
+    };
+
+    let path = std::env::temp_dir().join(format!("klask-log-{}", uuid::Uuid::new_v4()));
+    fs::write(&path, "earlier run\n").unwrap();
+
+    // Only the output of the child is read back, not the note or earlier runs
+    let (mut writer, mut reader, _) = output_files(Some(&path), Some("with -v")).unwrap();
+    writer.write_all(b"output\n").unwrap();
+    let mut read = String::new();
+    reader.read_to_string(&mut read).unwrap();
+    assert_eq!(read, "output\n");
+
+    let log = fs::read_to_string(&path).unwrap();
+    assert_eq!(log, "earlier run\n# Note: with -v\noutput\n");
+    drop(fs::remove_file(&path));
+}
+
+#[cfg(unix)]
+#[test]
+fn shell_values_are_quoted() {
+    use super::{shell_command, shell_line, shell_preview};
+    let args = ["%s\n", "a b", "$HOME;echo 'x'", "*", "`ls`|cat"].map(String::from);
+    let line = shell_line("printf", &args, "");
+    let output = shell_command(&line).output().unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "a b\n$HOME;echo 'x'\n*\n`ls`|cat\n"
+    );
+
+    // Only the suffix is interpreted
+    let line = shell_line("printf", &args[..3], "| tr a-z A-Z");
+    let output = shell_command(&line).output().unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "A B\n$HOME;ECHO 'X'\n"
+    );
+
+    // The preview runs the same line
+    let preview = shell_preview(&line);
+    let output = shell_command(&preview).output().unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "A B\n$HOME;ECHO 'X'\n"
+    );
+}
+
+#[test]
+fn cmd_quoting() {
+    use super::cmd_quote;
+
+    assert_eq!(cmd_quote("plain"), r#"^"plain^""#);
+    assert_eq!(cmd_quote("a & b|c"), r#"^"a ^& b^|c^""#);
+    assert_eq!(cmd_quote("%PATH%!"), r#"^"^%PATH^%^!^""#);
+    // Backslashes are doubled only before quotes
+    assert_eq!(cmd_quote(r#"C:\dir\ "q""#), r#"^"C:\dir\ \^"q\^"^""#);
+    assert_eq!(cmd_quote(r"C:\dir\"), r#"^"C:\dir\\^""#);
+}
+
+#[cfg(unix)]
+#[test]
+fn overlapping_in_process_runs() {
+    use super::{ChildApp, InProcess};
+    use eframe::egui;
+    use std::{
+        sync::{mpsc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    let matches = clap::Command::new("test").get_matches_from(["test"]);
+    let (release, wait) = mpsc::channel::<()>();
+    let wait = Mutex::new(wait);
+    let blocking = InProcess::new(move |_| wait.lock().unwrap().recv());
+    let quick = InProcess::new(|_| Ok::<_, String>(()));
+
+    let mut first =
+        ChildApp::run_in_process(blocking, matches.clone(), egui::Context::default()).unwrap();
+    // Would point stdout and stderr away from the first closure
+    assert!(
+        ChildApp::run_in_process(quick.clone(), matches.clone(), egui::Context::default()).is_err()
+    );
+
+    release.send(()).unwrap();
+    while first.exit_status().is_none() {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let mut second = ChildApp::run_in_process(quick, matches, egui::Context::default()).unwrap();
+    while second.exit_status().is_none() {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(second.exit_status().unwrap().code(), Some(0));
+}
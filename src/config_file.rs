@@ -0,0 +1,374 @@
+use crate::settings::{
+    ArgSort, DefaultTab, Localization, RunningIndicator, ScrollBehavior, Settings,
+    SubcommandDisplay, Theme,
+};
+use eframe::egui::Color32;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use toml::{Table, Value};
+
+impl Settings {
+    /// Loads settings from a TOML file, starting from the defaults. Keys are the names of
+    /// the fields and `localization` is a table with the names of the [`Localization`]
+    /// fields. Callbacks, `style` and `extra_tabs` can't be set from a file. Optional
+    /// fields can only be set, not cleared.
+    /// - Colors are hex strings like `"#ff0000"`.
+    /// - Enums are names like `"required_first"` for [`ArgSort::RequiredFirst`].
+    /// - Paths like `custom_font` or `log_file` are relative to the settings file.
+    /// - `repaint_interval` is in milliseconds.
+    /// - `conditional_visibility` is a list of `[trigger_id, trigger_value, [ids]]`.
+    ///
+    /// Settings next to the executable, named like `my-app.klask.toml` for `my-app`
+    /// or `my-app.exe`, are loaded by [`crate::run_app`] and [`crate::run_app_in_process`]
+    /// and override the ones passed in code.
+    /// Requires the `config-file` feature.
+    /// ```toml
+    /// enable_env = "Variables for the program"
+    /// arg_sort = "required_first"
+    /// output_color_map = { ERROR = "#ff0000" }
+    ///
+    /// [localization]
+    /// run = "Start"
+    /// ```
+    pub fn from_config_file(path: impl AsRef<Path>) -> io::Result<Settings> {
+        let mut settings = Settings::default();
+        settings.merge_config_file(path.as_ref())?;
+        Ok(settings)
+    }
+
+    /// Merges the settings file next to the executable, if there is one.
+    /// Errors are printed to stderr, the settings from code are used then.
+    pub(crate) fn with_exe_config_file(mut self) -> Self {
+        if let Some(path) = std::env::current_exe()
+            .ok()
+            .map(|exe| exe.with_extension("klask.toml"))
+            .filter(|path| path.exists())
+        {
+            if let Err(err) = self.merge_config_file(&path) {
+                // Shown without the `log` feature too, the file would be ignored silently
+                eprintln!("Couldn't load the settings: {err}");
+            }
+        }
+        self
+    }
+
+    /// Overrides the settings that are set in the file, see [`Settings::from_config_file`].
+    /// Nothing is changed if any key can't be applied.
+    pub(crate) fn merge_config_file(&mut self, path: &Path) -> io::Result<()> {
+        let invalid = |err: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {err}", path.display()),
+            )
+        };
+
+        let table: Table = fs::read_to_string(path)?
+            .parse()
+            .map_err(|err: toml::de::Error| invalid(err.to_string()))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut merged = self.clone();
+        merged.merge_table(table, dir).map_err(invalid)?;
+        *self = merged;
+        Ok(())
+    }
+
+    fn merge_table(&mut self, table: Table, dir: &Path) -> Result<(), String> {
+        for (key, value) in table {
+            let field = Field { key: &key, value };
+            match key.as_str() {
+                "enable_env" => self.enable_env = Some(field.string()?),
+                "env_default" => self.env_default = field.string_table()?.into_iter().collect(),
+                "known_env_vars" => self.known_env_vars = field.strings()?,
+                "enable_stdin" => self.enable_stdin = Some(field.string()?),
+                "stdin_default" => self.stdin_default = Some(field.string()?),
+                "stdin_description_placeholder" => {
+                    self.stdin_description_placeholder = Some(field.string()?)
+                }
+                "stdin_max_bytes" => self.stdin_max_bytes = Some(field.usize()?),
+                "enable_working_dir" => self.enable_working_dir = Some(field.string()?),
+                "working_dir_default" => self.working_dir_default = Some(dir.join(field.path()?)),
+                "working_dir_read_only" => self.working_dir_read_only = field.bool()?,
+                "default_tab" => {
+                    self.default_tab = field.choice(&[
+                        ("arguments", DefaultTab::Arguments),
+                        ("env", DefaultTab::Env),
+                        ("stdin", DefaultTab::Stdin),
+                    ])?
+                }
+                "custom_font" => {
+                    let path = dir.join(field.path()?);
+                    let data = fs::read(&path)
+                        .map_err(|err| format!("`{key}` {}: {err}", path.display()))?;
+                    self.custom_font = Some(Cow::Owned(data));
+                }
+                "disable_file_dialogs" => self.disable_file_dialogs = field.bool()?,
+                "enable_env_paste" => self.enable_env_paste = field.bool()?,
+                "env_paste_filter" => self.env_paste_filter = Some(field.string()?),
+                "usage_stats_path" => self.usage_stats_path = Some(dir.join(field.path()?)),
+                "field_history_path" => self.field_history_path = Some(dir.join(field.path()?)),
+                "inline_validation_errors" => self.inline_validation_errors = field.bool()?,
+                "inline_long_help" => self.inline_long_help = field.bool()?,
+                "arg_sort" => {
+                    self.arg_sort = field.choice(&[
+                        ("definition", ArgSort::Definition),
+                        ("alphabetical", ArgSort::Alphabetical),
+                        ("required_first", ArgSort::RequiredFirst),
+                        ("optional_first", ArgSort::OptionalFirst),
+                    ])?
+                }
+                "arg_order" => self.arg_order = field.strings()?,
+                "conditional_visibility" => {
+                    self.conditional_visibility = field.conditional_visibility()?
+                }
+                "error_color" => self.error_color = field.color()?,
+                "warning_color" => self.warning_color = field.color()?,
+                "run_button_ready_color" => self.run_button_ready_color = Some(field.color()?),
+                "enable_run_notes" => self.enable_run_notes = field.bool()?,
+                "persist_notes" => self.persist_notes = field.bool()?,
+                "compress_old_output" => self.compress_old_output = field.bool()?,
+                "clear_output_on_run" => self.clear_output_on_run = field.bool()?,
+                "output_line_separator" => self.output_line_separator = field.string()?,
+                "run_separator_timestamp" => self.run_separator_timestamp = field.bool()?,
+                "hide_output_on_run" => self.hide_output_on_run = field.bool()?,
+                "output_max_lines_before_compress" => {
+                    self.output_max_lines_before_compress = field.usize()?
+                }
+                "expected_output_lines" => self.expected_output_lines = Some(field.usize()?),
+                "output_strip_ansi" => self.output_strip_ansi = field.bool()?,
+                "output_binary_as_hex" => self.output_binary_as_hex = field.bool()?,
+                "output_color_map" => self.output_color_map = field.color_table()?,
+                "output_highlight_case_sensitive" => {
+                    self.output_highlight_case_sensitive = field.bool()?
+                }
+                "enable_copy_json" => self.enable_copy_json = field.bool()?,
+                "enable_output_popout" => self.enable_output_popout = field.bool()?,
+                "enable_go_to_missing" => self.enable_go_to_missing = field.bool()?,
+                "enable_changes_summary" => self.enable_changes_summary = field.bool()?,
+                "enable_detach" => self.enable_detach = field.bool()?,
+                "shell_suffix" => self.shell_suffix = Some(field.string()?),
+                "enable_field_errors" => self.enable_field_errors = field.bool()?,
+                "log_file" => self.log_file = Some(dir.join(field.path()?)),
+                "output_scroll_behavior" => {
+                    self.output_scroll_behavior = field.choice(&[
+                        ("always_bottom", ScrollBehavior::AlwaysBottom),
+                        ("always_top", ScrollBehavior::AlwaysTop),
+                        ("user_controlled", ScrollBehavior::UserControlled),
+                    ])?
+                }
+                "enable_save_output" => self.enable_save_output = field.bool()?,
+                "output_file_extension" => self.output_file_extension = field.string()?,
+                "max_concurrent_runs" => self.max_concurrent_runs = field.usize()?,
+                "child_env_var" => self.child_env_var = Some(field.string()?),
+                "batch_arg" => self.batch_arg = Some(field.string()?),
+                #[cfg(feature = "randomize")]
+                "enable_randomize" => self.enable_randomize = field.bool()?,
+                #[cfg(feature = "print-args")]
+                "enable_print_args" => self.enable_print_args = field.bool()?,
+                #[cfg(feature = "man")]
+                "enable_man_page" => self.enable_man_page = field.bool()?,
+                "confirm_close_while_running" => self.confirm_close_while_running = field.bool()?,
+                "kill_on_close" => self.kill_on_close = field.bool()?,
+                "show_help_panel" => self.show_help_panel = field.bool()?,
+                "subcommand_display" => {
+                    self.subcommand_display = field.choice(&[
+                        ("dropdown", SubcommandDisplay::Dropdown),
+                        ("side_panel", SubcommandDisplay::SidePanel),
+                    ])?
+                }
+                "window_always_on_top" => self.window_always_on_top = field.bool()?,
+                "combine_short_flags" => self.combine_short_flags = field.bool()?,
+                "infer_path_from_name" => self.infer_path_from_name = field.bool()?,
+                "repaint_interval" => {
+                    let millis = field.usize()? as u64;
+                    self.repaint_interval = Some(Duration::from_millis(millis));
+                }
+                "running_indicator" => {
+                    self.running_indicator = field.choice(&[
+                        ("dots", RunningIndicator::Dots),
+                        ("spinner", RunningIndicator::Spinner),
+                        ("elapsed", RunningIndicator::Elapsed),
+                    ])?
+                }
+                "show_run_status" => self.show_run_status = field.bool()?,
+                "template_vars" => self.template_vars = field.string_table()?,
+                "localization" => merge_localization(&mut self.localization, field.table()?)?,
+                "auto_detect_locale" => self.auto_detect_locale = field.bool()?,
+                "theme" => {
+                    self.theme = Some(field.choice(&[
+                        ("dark", Theme::Dark),
+                        ("light", Theme::Light),
+                        ("high_contrast", Theme::HighContrast),
+                    ])?)
+                }
+                "compact_mode" => self.compact_mode = field.bool()?,
+                "enable_compact_toggle" => self.enable_compact_toggle = field.bool()?,
+                _ => return Err(format!("unknown setting `{key}`")),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn merge_localization(localization: &mut Localization, table: Table) -> Result<(), String> {
+    for (key, value) in table {
+        let name = format!("localization.{key}");
+        let field = Field { key: &name, value };
+        match key.as_str() {
+            "error_is_required" => {
+                let [a, b] = field.string_array()?;
+                localization.error_is_required = (a, b);
+            }
+            "disabled_by_conflict" => {
+                let [a, b] = field.string_array()?;
+                localization.disabled_by_conflict = (a, b);
+            }
+            "error_too_few_values" => {
+                let [a, b, c] = field.string_array()?;
+                localization.error_too_few_values = (a, b, c);
+            }
+            "error_too_many_values" => {
+                let [a, b, c] = field.string_array()?;
+                localization.error_too_many_values = (a, b, c);
+            }
+            _ => match localization
+                .string_fields_mut()
+                .into_iter()
+                .find(|(field_name, _)| *field_name == key)
+            {
+                Some((_, string)) => *string = field.string()?,
+                None => return Err(format!("unknown setting `{name}`")),
+            },
+        }
+    }
+    Ok(())
+}
+
+/// A value of the file with its key for error messages.
+struct Field<'a> {
+    key: &'a str,
+    value: Value,
+}
+
+impl Field<'_> {
+    fn error(&self, expected: &str) -> String {
+        format!(
+            "`{}` should be {expected}, found {}",
+            self.key,
+            self.value.type_str()
+        )
+    }
+
+    fn bool(self) -> Result<bool, String> {
+        self.value.as_bool().ok_or_else(|| self.error("a boolean"))
+    }
+
+    fn string(self) -> Result<String, String> {
+        match self.value {
+            Value::String(s) => Ok(s),
+            _ => Err(self.error("a string")),
+        }
+    }
+
+    fn path(self) -> Result<PathBuf, String> {
+        self.string().map(PathBuf::from)
+    }
+
+    fn usize(self) -> Result<usize, String> {
+        self.value
+            .as_integer()
+            .and_then(|i| usize::try_from(i).ok())
+            .ok_or_else(|| self.error("a positive integer"))
+    }
+
+    fn color(self) -> Result<Color32, String> {
+        let key = self.key;
+        let hex = self.string()?;
+        Color32::from_hex(&hex)
+            .map_err(|_| format!("`{key}` should be a color like \"#ff0000\", found \"{hex}\""))
+    }
+
+    fn table(self) -> Result<Table, String> {
+        match self.value {
+            Value::Table(table) => Ok(table),
+            _ => Err(self.error("a table")),
+        }
+    }
+
+    fn array(self) -> Result<Vec<Value>, String> {
+        match self.value {
+            Value::Array(array) => Ok(array),
+            _ => Err(self.error("an array")),
+        }
+    }
+
+    fn strings(self) -> Result<Vec<String>, String> {
+        let key = self.key;
+        self.array()?
+            .into_iter()
+            .map(|value| Field { key, value }.string())
+            .collect()
+    }
+
+    fn string_array<const N: usize>(self) -> Result<[String; N], String> {
+        let key = self.key;
+        self.strings()?
+            .try_into()
+            .map_err(|_| format!("`{key}` should be an array of {N} strings"))
+    }
+
+    fn string_table(self) -> Result<HashMap<String, String>, String> {
+        let key = self.key;
+        self.table()?
+            .into_iter()
+            .map(|(name, value)| Ok((name, Field { key, value }.string()?)))
+            .collect()
+    }
+
+    fn color_table(self) -> Result<HashMap<String, Color32>, String> {
+        let key = self.key;
+        self.table()?
+            .into_iter()
+            .map(|(name, value)| Ok((name, Field { key, value }.color()?)))
+            .collect()
+    }
+
+    fn conditional_visibility(self) -> Result<Vec<(String, String, Vec<String>)>, String> {
+        let error = self.error("a list of [trigger_id, trigger_value, [ids]]");
+        let key = self.key;
+        self.array()?
+            .into_iter()
+            .map(|entry| {
+                let Value::Array(entry) = entry else {
+                    return Err(error.clone());
+                };
+                let [id, value, ids]: [Value; 3] = entry.try_into().map_err(|_| error.clone())?;
+                Ok((
+                    Field { key, value: id }.string()?,
+                    Field { key, value }.string()?,
+                    Field { key, value: ids }.strings()?,
+                ))
+            })
+            .collect()
+    }
+
+    fn choice<T: Copy>(self, choices: &[(&str, T)]) -> Result<T, String> {
+        let names: Vec<_> = choices
+            .iter()
+            .map(|(name, _)| format!("\"{name}\""))
+            .collect();
+        let expected = format!("one of {}", names.join(", "));
+        let key = self.key;
+        let error = self.error(&expected);
+        let name = self.string().map_err(|_| error)?;
+        choices
+            .iter()
+            .find(|(choice, _)| *choice == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| format!("`{key}` should be {expected}, found \"{name}\""))
+    }
+}
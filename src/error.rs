@@ -2,33 +2,25 @@
 pub enum ExecutionError {
     #[error("Internal io error: {0}")]
     IoError(#[from] std::io::Error),
-    #[error("Internal error: no name in validation")]
-    NoValidationName,
     #[error("Internal match error: {0}")]
     MatchError(clap::Error),
     #[error("Internal error: no child stdout or stderr")]
     NoStdoutOrStderr,
+    /// `path` has the names of the subcommands the arg is in, so args with
+    /// the same name at different levels aren't confused
     #[error("Validation error in {}: '{}'", .name, .message)]
-    ValidationError { name: String, message: String },
+    ValidationError {
+        path: Vec<String>,
+        name: String,
+        message: String,
+    },
     #[error("{0}")]
     GuiError(String),
 }
 
 impl From<clap::Error> for ExecutionError {
     fn from(err: clap::Error) -> Self {
-        match clap::Error::kind(&err) {
-            clap::error::ErrorKind::ValueValidation => {
-                if let Some((kind, value)) = err.context().next() {
-                    Self::ValidationError {
-                        name: kind.to_string(),
-                        message: value.to_string(),
-                    }
-                } else {
-                    Self::NoValidationName
-                }
-            }
-            _ => Self::MatchError(err),
-        }
+        Self::MatchError(err)
     }
 }
 
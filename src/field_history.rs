@@ -0,0 +1,126 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs, io,
+    iter::Peekable,
+    path::Path,
+    str::Chars,
+};
+
+/// Number of distinct values remembered for each field.
+pub const MAX_ENTRIES: usize = 10;
+
+/// Key of the run notes next to the arg ids, see [`crate::Settings::enable_run_notes`].
+pub const NOTES_KEY: &str = "klask:notes";
+
+/// Values previously entered into each field, most recent first. Stored as a
+/// small toml-like file with one line per entry:
+/// ```toml
+/// "name" = ["klask"]
+/// "tags" = ["a", "b"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldHistory {
+    pub fields: BTreeMap<String, VecDeque<Vec<String>>>,
+}
+
+impl FieldHistory {
+    /// A missing or unreadable file is treated as empty history.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .map(|s| Self::parse(&s))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = String::new();
+        for (id, entries) in &self.fields {
+            for entry in entries {
+                out.push_str(&format!("{id:?} = {entry:?}\n"));
+            }
+        }
+        fs::write(path, out)
+    }
+
+    pub(crate) fn parse(s: &str) -> Self {
+        let mut history = Self::default();
+
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((id, entry)) = parse_line(line) {
+                let entries = history.fields.entry(id).or_default();
+                if entries.len() < MAX_ENTRIES {
+                    entries.push_back(entry);
+                }
+            }
+        }
+
+        history
+    }
+}
+
+/// Moves `entry` to the front of `history`, dropping the oldest entries past [`MAX_ENTRIES`].
+pub fn push(history: &mut VecDeque<Vec<String>>, entry: Vec<String>) {
+    history.retain(|e| *e != entry);
+    history.push_front(entry);
+    history.truncate(MAX_ENTRIES);
+}
+
+fn parse_line(line: &str) -> Option<(String, Vec<String>)> {
+    let mut chars = line.chars().peekable();
+    let id = parse_string(&mut chars)?;
+
+    skip_whitespace(&mut chars);
+    expect(&mut chars, '=')?;
+    skip_whitespace(&mut chars);
+    expect(&mut chars, '[')?;
+
+    let mut entry = vec![];
+    loop {
+        skip_whitespace(&mut chars);
+        match chars.peek()? {
+            ']' => break,
+            ',' => {
+                chars.next();
+            }
+            _ => entry.push(parse_string(&mut chars)?),
+        }
+    }
+
+    Some((id, entry))
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Option<()> {
+    (chars.next()? == expected).then_some(())
+}
+
+/// Parses a quoted string with the escapes written by `{:?}`.
+fn parse_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    expect(chars, '"')?;
+
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => s.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'u' => {
+                    expect(chars, '{')?;
+                    let hex: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                }
+                c => c,
+            }),
+            c => s.push(c),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use clap::{builder::PossibleValuesParser, value_parser, Arg, ArgAction, ArgMatches, Command};
+use serde_json::{Map, Value};
+
+/// Builds a [`Command`] with an argument for every property of the schema.
+/// The name is taken from `title` and the about text from `description`.
+pub fn command(schema: &Value) -> Command {
+    let name = schema
+        .get("title")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .or_else(|| {
+            std::env::current_exe()
+                .ok()?
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+
+    let mut command = Command::new(name).args(args(schema));
+    if let Some(about) = schema.get("description").and_then(Value::as_str) {
+        command = command.about(about.to_string());
+    }
+    command
+}
+
+/// Maps JSON Schema properties to args. `string`, `integer`, `number`,
+/// `boolean` and `array` (of strings) types are supported, together with
+/// `description`, `default`, `enum`, `minimum` and `maximum`.
+pub fn args(schema: &Value) -> Vec<Arg> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    properties(schema)
+        .map(|(name, property)| {
+            let mut arg = Arg::new(name).long(name);
+
+            if let Some(desc) = property.get("description").and_then(Value::as_str) {
+                arg = arg.help(desc.to_string());
+            }
+
+            match property_type(property) {
+                "boolean" => return arg.action(ArgAction::SetTrue),
+                "array" => {
+                    arg = arg.action(ArgAction::Append);
+                    if let Some(items) = property.get("items") {
+                        arg = with_enum(arg, items);
+                    }
+                }
+                "integer" => {
+                    let min = property.get("minimum").and_then(Value::as_i64);
+                    let max = property.get("maximum").and_then(Value::as_i64);
+                    let range = min.unwrap_or(i64::MIN)..=max.unwrap_or(i64::MAX);
+                    arg = arg
+                        .action(ArgAction::Set)
+                        .value_parser(value_parser!(i64).range(range));
+                }
+                "number" => {
+                    arg = arg.action(ArgAction::Set).value_parser(value_parser!(f64));
+                }
+                _ => arg = with_enum(arg.action(ArgAction::Set), property),
+            }
+
+            match property.get("default") {
+                Some(Value::Array(values)) => {
+                    arg = arg.default_values(values.iter().map(value_to_string))
+                }
+                Some(Value::Null) | None => {}
+                Some(value) => arg = arg.default_value(value_to_string(value)),
+            }
+
+            arg.required(required.contains(&name.as_str()))
+        })
+        .collect()
+}
+
+/// Converts matches of a [`command`] back into a JSON object.
+pub fn to_value(schema: &Value, matches: &ArgMatches) -> Value {
+    let mut object = Map::new();
+
+    for (name, property) in properties(schema) {
+        let value = match property_type(property) {
+            "boolean" => Some(Value::Bool(matches.get_flag(name))),
+            "integer" => matches.get_one::<i64>(name).map(|&i| i.into()),
+            "number" => matches.get_one::<f64>(name).map(|&f| f.into()),
+            "array" => matches
+                .get_many::<String>(name)
+                .map(|values| values.cloned().collect::<Vec<_>>().into()),
+            _ => matches.get_one::<String>(name).map(|s| s.clone().into()),
+        };
+
+        if let Some(value) = value {
+            object.insert(name.clone(), value);
+        }
+    }
+
+    Value::Object(object)
+}
+
+fn properties(schema: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
+
+fn property_type(property: &Value) -> &str {
+    property
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or("string")
+}
+
+fn with_enum(arg: Arg, property: &Value) -> Arg {
+    match property.get("enum").and_then(Value::as_array) {
+        Some(values) => arg.value_parser(PossibleValuesParser::new(
+            values.iter().map(value_to_string).collect::<Vec<_>>(),
+        )),
+        None => arg,
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
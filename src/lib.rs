@@ -25,88 +25,190 @@
 //! }
 //! ```
 
+/// Forwards to the `log` crate with the `log` feature. Compiles to nothing otherwise.
+#[cfg(feature = "log")]
+macro_rules! log {
+    ($level:ident, $($arg:tt)+) => {
+        ::log::$level!(target: "klask", $($arg)+)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log {
+    ($level:ident, $($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
 mod app_state;
 mod arg_state;
 mod child_app;
+#[cfg(feature = "config-file")]
+mod config_file;
 mod error;
+mod field_history;
+#[cfg(feature = "json-schema")]
+mod json_schema;
 /// Additional options for output like progress bars.
 pub mod output;
+mod panel;
+#[cfg(feature = "print-args")]
+mod print_args;
 mod settings;
+mod usage_stats;
 
 use app_state::AppState;
-use child_app::{ChildApp, StdinType};
+use arg_state::ArgSettings;
+use child_app::{ChildApp, InProcess, StdinType};
 use clap::{ArgMatches, Command, CommandFactory, FromArgMatches};
 use eframe::{
-    egui::{self, Button, Color32, Context, FontData, FontDefinitions, Grid, Style, TextEdit, Ui},
+    egui::{
+        self, Button, CollapsingHeader, Color32, Context, FontData, FontDefinitions, Grid, Key,
+        Modifiers, ProgressBar, RichText, Style, TextEdit, Ui, ViewportBuilder, ViewportClass,
+        ViewportCommand, ViewportId,
+    },
     CreationContext, Frame,
 };
 use error::ExecutionError;
 use rfd::FileDialog;
 
-use output::Output;
-pub use settings::{Localization, Settings};
-use std::{borrow::Cow, hash::Hash};
+use field_history::{FieldHistory, NOTES_KEY};
+use output::{KeywordColors, Output, OutputSettings};
+pub use panel::KlaskPanel;
+pub use settings::{
+    ArgSort, DefaultTab, EnvExtraUi, ExtraTab, Localization, RunningIndicator, ScrollBehavior,
+    Settings, SettingsBuilder, StdinExtraUi, SubcommandDisplay, TabContent, Theme, Validator,
+    WindowCloseHook,
+};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Display,
+    hash::Hash,
+    iter,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+#[cfg(feature = "man")]
+use std::{io, time::Instant};
+use usage_stats::UsageStats;
 
 const CHILD_APP_ENV_VAR: &str = "KLASK_CHILD_APP";
 
+static CURRENT_STEP: AtomicUsize = AtomicUsize::new(0);
+
+/// Index of the [`Settings::run_sequence`] step that is running. 0 is the main
+/// command, 1 is the first command in `run_sequence` and so on.
+/// ```no_run
+/// # use clap::Command;
+/// # use klask::Settings;
+/// let mut settings = Settings::default();
+/// settings.run_sequence.push(|| Command::new("Upload"));
+///
+/// klask::run_app(Command::new("Build"), settings, |matches| {
+///     match klask::current_step() {
+///         0 => println!("Building"),
+///         _ => println!("Uploading"),
+///     }
+/// });
+/// ```
+pub fn current_step() -> usize {
+    CURRENT_STEP.load(Ordering::Relaxed)
+}
+
 /// Call with an [`App`] and a closure that contains the code that would normally be in `main`.
 /// ```no_run
 /// # use clap::{Command, arg};
 /// # use klask::Settings;
 /// let app = Command::new("Example").arg(arg!(--debug <VALUE>).short('d'));
-
+///
 /// klask::run_app(app, Settings::default(), |matches| {
 ///    println!("{:?}", matches.try_contains_id("debug"))
 /// });
 /// ```
 pub fn run_app(app: Command, settings: Settings, f: impl FnOnce(&ArgMatches)) {
-    if std::env::var(CHILD_APP_ENV_VAR).is_ok() {
-        std::env::remove_var(CHILD_APP_ENV_VAR);
+    // Before any setting is read, so the GUI and the relaunched binary agree
+    #[cfg(feature = "config-file")]
+    let settings = settings.with_exe_config_file();
+
+    if let Ok(step) = std::env::var(settings.child_env_var()) {
+        std::env::remove_var(settings.child_env_var());
+
+        #[allow(unused_mut)]
+        let mut args: Vec<_> = std::env::args_os().collect();
+        #[cfg(feature = "print-args")]
+        let print_args = settings.enable_print_args && print_args::take_flag(&mut args);
 
+        let (step, mut app) = settings.child_command(app, &step);
+        CURRENT_STEP.store(step, Ordering::Relaxed);
         let matches = app
-            .try_get_matches()
+            .try_get_matches_from_mut(args)
             .expect("Internal error, arguments should've been verified by the GUI app");
 
+        #[cfg(feature = "print-args")]
+        if print_args {
+            println!("{}", print_args::to_value(&app, &matches));
+            std::process::exit(0);
+        }
+
         f(&matches);
     } else {
-        // During validation we don't pass in a binary name
-        let app = app.no_binary_name(true);
-        let app_name = app.get_name().to_string();
-
-        // eframe::run_native requires that Box::new(klask) has 'static
-        // lifetime, so we must leak here. But it never returns (return value !)
-        // so it should be ok.
-        let localization = Box::leak(Box::new(settings.localization));
-
-        let mut klask = Klask {
-            state: AppState::new(&app, localization),
-            tab: Tab::Arguments,
-            env: settings.enable_env.map(|desc| (desc, vec![])),
-            stdin: settings
-                .enable_stdin
-                .map(|desc| (desc, StdinType::Text(String::new()))),
-            working_dir: settings
-                .enable_working_dir
-                .map(|desc| (desc, String::new())),
-            output: Output::None,
-            app,
-            custom_font: settings.custom_font,
-            localization,
-            style: settings.style,
-        };
-        let native_options = eframe::NativeOptions::default();
-        eframe::run_native(
-            app_name.as_str(),
-            native_options,
-            Box::new(|cc| {
-                klask.setup(cc);
-                Box::new(klask)
-            }),
-        )
-        .unwrap();
+        run_gui(app, settings, None);
     }
 }
 
+/// Like [`run_app`], but the closure is called on a thread of the GUI process instead of
+/// relaunching the binary. Output printed to stdout and stderr is captured on unix only.
+/// The environment variables, stdin and working directory settings are ignored, and a
+/// running closure can't be killed.
+///
+/// An error returned by the closure is shown under the output and the run
+/// exits with code 1.
+/// ```no_run
+/// # use clap::{Command, arg};
+/// # use klask::Settings;
+/// let app = Command::new("Example").arg(arg!(--path <VALUE>));
+///
+/// klask::run_app_in_process(app, Settings::default(), |matches| {
+///     let path = matches.get_one::<String>("path").unwrap();
+///     let text = std::fs::read_to_string(path)?;
+///     println!("{text}");
+///     Ok::<_, std::io::Error>(())
+/// });
+/// ```
+pub fn run_app_in_process<E: Display>(
+    app: Command,
+    settings: Settings,
+    f: impl Fn(&ArgMatches) -> Result<(), E> + Send + Sync + 'static,
+) {
+    #[cfg(feature = "config-file")]
+    let settings = settings.with_exe_config_file();
+
+    run_gui(app, settings, Some(InProcess::new(f)));
+}
+
+fn run_gui(app: Command, settings: Settings, in_process: Option<InProcess>) {
+    let app_name = app.get_name().to_string();
+    let native_options = settings.native_options();
+    let mut klask = Klask::new(app, settings, in_process);
+    klask.assert_localization();
+    eframe::run_native(
+        app_name.as_str(),
+        native_options,
+        Box::new(|cc| {
+            klask.setup(cc);
+            Box::new(klask)
+        }),
+    )
+    .unwrap();
+}
+
 /// Can be used with a struct deriving [`clap::Clap`]. Call with a closure that contains the code that would normally be in `main`.
 /// It's just a wrapper over [`run_app`].
 /// ```no_run
@@ -134,24 +236,223 @@ where
     });
 }
 
+/// Creates the GUI from a JSON Schema object instead of a [`Command`]. Every property
+/// becomes an argument. The closure receives a JSON object with the entered values.
+/// Requires the `json-schema` feature.
+/// ```no_run
+/// # use klask::Settings;
+/// let schema = serde_json::json!({
+///     "title": "Example",
+///     "properties": {
+///         "name": { "type": "string", "description": "Your name" },
+///         "count": { "type": "integer", "minimum": 1, "default": 1 },
+///         "loud": { "type": "boolean" }
+///     },
+///     "required": ["name"]
+/// });
+///
+/// klask::run_app_from_schema(schema, Settings::default(), |value| {
+///     println!("{}", value["name"]);
+/// });
+/// ```
+#[cfg(feature = "json-schema")]
+pub fn run_app_from_schema(
+    schema: serde_json::Value,
+    settings: Settings,
+    f: impl FnOnce(serde_json::Value),
+) {
+    run_app(json_schema::command(&schema), settings, |matches| {
+        f(json_schema::to_value(&schema, matches))
+    });
+}
+
+/// Roff source of the man page of `app`
+#[cfg(feature = "man")]
+fn render_man_page(app: &Command) -> io::Result<String> {
+    let mut page = vec![];
+    clap_mangen::Man::new(app.clone()).render(&mut page)?;
+    Ok(String::from_utf8_lossy(&page).into_owned())
+}
+
 #[derive(Debug)]
-struct Klask<'s> {
-    state: AppState<'s>,
+struct Klask {
+    state: AppState,
+    /// Commands of [`Settings::run_sequence`] with their forms
+    steps: Vec<(Command, AppState)>,
+    /// Sequence of every output slot
+    sequences: Vec<Sequence>,
+    /// Selected tab, kept while it's hidden from the view menu
     tab: Tab,
     /// First string is a description
     env: Option<(String, Vec<(String, String)>)>,
+    /// Environment variables read by the program, with the name of the arg they set
+    known_env_vars: BTreeMap<String, Option<String>>,
     /// First string is a description
     stdin: Option<(String, StdinType)>,
+    stdin_max_bytes: Option<usize>,
+    stdin_placeholder: Option<String>,
     /// First string is a description
     working_dir: Option<(String, String)>,
-    output: Output,
+    working_dir_read_only: bool,
+    extra_tabs: Vec<ExtraTab>,
+    /// None when the command has no version, author or about
+    about: Option<About>,
+    /// The default working directory didn't exist at startup, until it's edited
+    working_dir_missing: bool,
+    /// One slot per concurrent run, the string is the tab label
+    outputs: Vec<(Output, String)>,
+    /// Index of the visible output
+    current_output: usize,
+    run_button_ready_color: Option<Color32>,
+    /// Command lines of the main command and steps when they were last checked,
+    /// and whether they were valid
+    run_ready: (Vec<Option<Vec<String>>>, bool),
+    repaint_interval: Option<Duration>,
+    running_indicator: RunningIndicator,
+    show_run_status: bool,
+    confirm_close: bool,
+    show_close_dialog: bool,
+    kill_on_close: bool,
+    show_help_panel: bool,
+    on_window_close: Option<WindowCloseHook>,
+    error_color: Color32,
+    warning_color: Color32,
+    output_settings: OutputSettings,
+    clear_output_on_run: bool,
+    output_line_separator: String,
+    run_separator_timestamp: bool,
+    hide_output_on_run: bool,
+    /// Extension for the save dialog, Some when saving the output is enabled
+    save_output_extension: Option<String>,
+    /// The hidden output of the last run was shown with the expand button
+    output_expanded: bool,
+    /// The output is shown in its own window instead of below the form
+    output_popped_out: bool,
+    usage_stats: Option<(UsageStats, PathBuf)>,
+    /// Output index and changed arg ids of current runs, recorded once they succeed
+    pending_usage: Vec<(usize, Vec<String>)>,
+    /// History loaded from [`Settings::field_history_path`] and the path
+    field_history: Option<(Arc<FieldHistory>, PathBuf)>,
+    /// Some when [`Settings::enable_man_page`] is set, without `no_binary_name`
+    #[cfg(feature = "man")]
+    man_page_app: Option<Command>,
+    /// When and where the man page was last saved
+    #[cfg(feature = "man")]
+    man_page_saved: Option<(Instant, PathBuf)>,
+    show_usage_stats: bool,
+    /// Some when run notes are enabled
+    notes: Option<String>,
+    /// Notes of earlier runs, most recent first, saved with the field history
+    notes_history: VecDeque<Vec<String>>,
+    persist_notes: bool,
     // This isn't a generic lifetime because eframe::run_native() requires
     // a 'static lifetime because boxed trait objects default to 'static
     app: Command,
 
     custom_font: Option<Cow<'static, [u8]>>,
-    localization: &'s Localization,
+    localization: Arc<Localization>,
     style: Style,
+    theme: Option<Theme>,
+    compact_mode: bool,
+    enable_compact_toggle: bool,
+    /// Toggled from the view menu. Hidden tabs keep their data and are still used when running
+    show_env: bool,
+    show_stdin: bool,
+    show_working_dir: bool,
+    disable_file_dialogs: bool,
+    env_extra_ui: Vec<EnvExtraUi>,
+    stdin_extra_ui: Vec<StdinExtraUi>,
+    validators: HashMap<String, Validator>,
+    batch_arg: Option<String>,
+    #[cfg(feature = "randomize")]
+    enable_randomize: bool,
+    enable_copy_json: bool,
+    enable_output_popout: bool,
+    enable_go_to_missing: bool,
+    enable_changes_summary: bool,
+    enable_detach: bool,
+    shell_suffix: Option<String>,
+    log_file: Option<PathBuf>,
+    child_env_var: String,
+    /// Some when running with [`run_app_in_process`]
+    in_process: Option<InProcess>,
+}
+
+/// Metadata of the program for the about menu, the GUI equivalent of `--version`
+#[derive(Debug)]
+struct About {
+    name: String,
+    version: Option<String>,
+    author: Option<String>,
+    about: Option<String>,
+}
+
+impl About {
+    fn new(app: &Command) -> Option<Self> {
+        let about = About {
+            name: app.get_display_name().unwrap_or(app.get_name()).to_string(),
+            // The long version usually has the build info
+            version: app
+                .get_long_version()
+                .or(app.get_version())
+                .map(ToString::to_string),
+            author: app.get_author().map(ToString::to_string),
+            about: app
+                .get_long_about()
+                .or(app.get_about())
+                .map(ToString::to_string),
+        };
+        (about.version.is_some() || about.author.is_some() || about.about.is_some())
+            .then_some(about)
+    }
+}
+
+/// Steps of a run with [`Settings::run_sequence`] or values of a run with
+/// [`Settings::batch_arg`]. The current step is the output in `Klask::outputs`.
+#[derive(Debug, Default)]
+struct Sequence {
+    /// Outputs of finished steps
+    finished: Vec<Output>,
+    /// Args of the steps that haven't started yet
+    remaining: Vec<Vec<String>>,
+    /// Value of the batch arg that is running
+    batch_item: Option<String>,
+    /// Values and args of batch runs that haven't started yet
+    batch: Vec<(String, Vec<String>)>,
+}
+
+impl Sequence {
+    /// Whether something still has to be done once the current output finishes
+    fn is_pending(&self) -> bool {
+        !self.remaining.is_empty() || self.batch_item.is_some()
+    }
+}
+
+fn batch_header(item: &str) -> String {
+    format!("--- {item} ---\n")
+}
+
+/// [`Settings::log_file`] of an output slot, with the number of the run added to the
+/// name after the first one, like `klask-2.log`
+fn slot_log_file(path: &Path, slot: usize) -> PathBuf {
+    if slot == 0 {
+        return path.to_path_buf();
+    }
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!("-{}", slot + 1));
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+/// Size of the stdin text or file in bytes. Files that can't be read count as empty.
+fn stdin_len(stdin: &StdinType) -> u64 {
+    match stdin {
+        StdinType::Text(text) => text.len() as u64,
+        StdinType::File(path) => std::fs::metadata(path).map_or(0, |m| m.len()),
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -159,125 +460,558 @@ enum Tab {
     Arguments,
     Env,
     Stdin,
+    /// Index into [`Settings::extra_tabs`]
+    Custom(usize),
 }
 
-impl eframe::App for Klask<'_> {
+impl Klask {
+    fn new(app: Command, mut settings: Settings, in_process: Option<InProcess>) -> Self {
+        // The man page needs the name of the binary
+        #[cfg(feature = "man")]
+        let man_page_app = settings.enable_man_page.then(|| app.clone());
+        // During validation we don't pass in a binary name
+        let app = app.no_binary_name(true);
+
+        if settings.auto_detect_locale {
+            if let Some(localization) = Localization::from_env() {
+                settings.localization = localization;
+            }
+        }
+
+        let child_env_var = settings.child_env_var().to_string();
+        let max_runs = settings.max_concurrent_runs(in_process.is_some());
+
+        // Shared by the args of every command, and by the outputs
+        let localization = Arc::new(settings.localization);
+        let template_vars = Arc::new(settings.template_vars);
+        let arg_order = Arc::from(settings.arg_order);
+        let keyword_colors = (!settings.output_color_map.is_empty()).then(|| {
+            let colors = KeywordColors::new(
+                settings.output_color_map,
+                settings.output_highlight_case_sensitive,
+            );
+            Arc::new(colors)
+        });
+        let conditional_visibility = Arc::from(settings.conditional_visibility);
+        let field_history = settings
+            .field_history_path
+            .map(|path| (Arc::new(FieldHistory::load(&path)), path));
+        let env_paste = settings
+            .enable_env_paste
+            .then(|| Arc::from(settings.env_paste_filter.unwrap_or_default()));
+
+        let notes_history = field_history
+            .as_ref()
+            .and_then(|(history, _)| history.fields.get(NOTES_KEY).cloned())
+            .unwrap_or_default();
+
+        let mut known_env_vars = settings
+            .known_env_vars
+            .into_iter()
+            .map(|var| (var, None))
+            .collect();
+        arg_env_vars(&app, &mut known_env_vars);
+
+        let arg_settings = ArgSettings {
+            inline_validation_errors: settings.inline_validation_errors,
+            inline_long_help: settings.inline_long_help,
+            error_color: settings.error_color,
+            warning_color: settings.warning_color,
+            disable_file_dialogs: settings.disable_file_dialogs,
+            template_vars: Some(template_vars),
+            arg_sort: settings.arg_sort,
+            arg_order,
+            conditional_visibility,
+            combine_short_flags: settings.combine_short_flags,
+            infer_path_from_name: settings.infer_path_from_name,
+            subcommand_display: settings.subcommand_display,
+            compact_mode: settings.compact_mode,
+            env_paste,
+            history: field_history
+                .as_ref()
+                .map(|(history, _)| Arc::clone(history)),
+        };
+
+        let outputs: Vec<_> = (1..=max_runs)
+            .map(|i| (Output::None, format!("{} {i}", localization.run)))
+            .collect();
+
+        // Closures in in-process mode don't know about steps
+        let steps = match &in_process {
+            Some(_) => vec![],
+            None => settings.run_sequence,
+        };
+        let steps = steps
+            .into_iter()
+            .map(|step| {
+                let step = step().no_binary_name(true);
+                let state = AppState::new(&step, &localization, arg_settings.clone());
+                (step, state)
+            })
+            .collect();
+
+        let tab = match settings.default_tab {
+            DefaultTab::Env if settings.enable_env.is_some() => Tab::Env,
+            DefaultTab::Stdin if settings.enable_stdin.is_some() => Tab::Stdin,
+            DefaultTab::Arguments => Tab::Arguments,
+            tab => {
+                log!(
+                    warn,
+                    "The {tab:?} tab isn't enabled, opening the arguments tab"
+                );
+                Tab::Arguments
+            }
+        };
+
+        Klask {
+            state: AppState::new(&app, &localization, arg_settings),
+            steps,
+            sequences: outputs.iter().map(|_| Sequence::default()).collect(),
+            tab,
+            env: settings.enable_env.map(|desc| (desc, settings.env_default)),
+            known_env_vars,
+            stdin: settings.enable_stdin.map(|desc| {
+                (
+                    desc,
+                    StdinType::Text(settings.stdin_default.unwrap_or_default()),
+                )
+            }),
+            stdin_placeholder: settings.stdin_description_placeholder,
+            stdin_max_bytes: settings.stdin_max_bytes,
+            working_dir_missing: settings
+                .working_dir_default
+                .as_ref()
+                .is_some_and(|dir| !dir.is_dir()),
+            working_dir: settings.enable_working_dir.map(|desc| {
+                let dir = settings.working_dir_default.unwrap_or_default();
+                (desc, dir.to_string_lossy().into_owned())
+            }),
+            working_dir_read_only: settings.working_dir_read_only,
+            extra_tabs: settings.extra_tabs,
+            about: About::new(&app),
+            outputs,
+            current_output: 0,
+            run_button_ready_color: settings.run_button_ready_color,
+            run_ready: (vec![], false),
+            repaint_interval: settings.repaint_interval,
+            running_indicator: settings.running_indicator,
+            show_run_status: settings.show_run_status,
+            confirm_close: settings.confirm_close_while_running,
+            kill_on_close: settings.kill_on_close,
+            show_help_panel: settings.show_help_panel,
+            on_window_close: settings.on_window_close,
+            show_close_dialog: false,
+            error_color: settings.error_color,
+            warning_color: settings.warning_color,
+            output_settings: OutputSettings {
+                compress_after: settings
+                    .compress_old_output
+                    .then_some(settings.output_max_lines_before_compress),
+                expected_lines: settings.expected_output_lines,
+                strip_ansi: settings.output_strip_ansi,
+                binary_as_hex: settings.output_binary_as_hex,
+                scroll_behavior: settings.output_scroll_behavior,
+                field_errors: settings.enable_field_errors,
+                keyword_colors,
+                error_color: settings.error_color,
+                slot: 0,
+                localization: Arc::clone(&localization),
+            },
+            clear_output_on_run: settings.clear_output_on_run,
+            output_line_separator: settings.output_line_separator,
+            run_separator_timestamp: settings.run_separator_timestamp,
+            hide_output_on_run: settings.hide_output_on_run,
+            save_output_extension: settings
+                .enable_save_output
+                .then_some(settings.output_file_extension),
+            output_expanded: false,
+            output_popped_out: false,
+            usage_stats: settings
+                .usage_stats_path
+                .map(|path| (UsageStats::load(&path), path)),
+            pending_usage: vec![],
+            field_history,
+            #[cfg(feature = "man")]
+            man_page_app,
+            #[cfg(feature = "man")]
+            man_page_saved: None,
+            notes: settings.enable_run_notes.then(String::new),
+            notes_history,
+            persist_notes: settings.persist_notes,
+            show_usage_stats: false,
+            app,
+            custom_font: settings.custom_font,
+            localization,
+            style: settings.style,
+            theme: settings.theme,
+            compact_mode: settings.compact_mode,
+            enable_compact_toggle: settings.enable_compact_toggle,
+            show_env: true,
+            show_stdin: true,
+            show_working_dir: true,
+            disable_file_dialogs: settings.disable_file_dialogs,
+            env_extra_ui: settings.env_extra_ui,
+            stdin_extra_ui: settings.stdin_extra_ui,
+            validators: settings.validators,
+            batch_arg: settings.batch_arg,
+            #[cfg(feature = "randomize")]
+            enable_randomize: settings.enable_randomize,
+            enable_copy_json: settings.enable_copy_json,
+            enable_output_popout: settings.enable_output_popout,
+            enable_go_to_missing: settings.enable_go_to_missing,
+            enable_changes_summary: settings.enable_changes_summary,
+            enable_detach: settings.enable_detach,
+            shell_suffix: settings.shell_suffix,
+            log_file: settings.log_file,
+            child_env_var,
+            in_process,
+        }
+    }
+}
+
+impl eframe::App for Klask {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::vertical().show(ui, |ui| {
+        if self.show_help_panel {
+            egui::SidePanel::right("klask_help")
+                .default_width(200.0)
+                .show(ctx, |ui| self.update_help_panel(ui));
+        }
+        egui::CentralPanel::default().show(ctx, |ui| self.ui(ui));
+        self.update_close_confirmation(ctx);
+    }
+}
+
+impl Klask {
+    /// Contents of the window, also shown by [`KlaskPanel`]
+    fn ui(&mut self, ui: &mut Ui) {
+        let ctx = &ui.ctx().clone();
+        let env_shown = self.env.is_some() && self.show_env;
+        let stdin_shown = self.stdin.is_some() && self.show_stdin;
+        let shown_tab = match self.tab {
+            Tab::Env if !env_shown => Tab::Arguments,
+            Tab::Stdin if !stdin_shown => Tab::Arguments,
+            tab => tab,
+        };
+        let mut tab = shown_tab;
+
+        // Every tab keeps its own scroll position
+        egui::ScrollArea::vertical()
+            .id_source(shown_tab)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    self.update_view_menu(ui);
+                    self.update_theme_menu(ui);
+                    self.update_about_menu(ui);
+                    #[cfg(feature = "man")]
+                    self.update_man_page_menu(ui);
+                });
+
                 // Tab selection
                 let tab_count =
-                    1 + usize::from(self.env.is_some()) + usize::from(self.stdin.is_some());
+                    1 + usize::from(env_shown) + usize::from(stdin_shown) + self.extra_tabs.len();
 
                 if tab_count > 1 {
                     ui.columns(tab_count, |ui| {
                         let mut index = 0;
 
                         ui[index].selectable_value(
-                            &mut self.tab,
+                            &mut tab,
                             Tab::Arguments,
                             &self.localization.arguments,
                         );
                         index += 1;
 
-                        if self.env.is_some() {
+                        if env_shown {
                             ui[index].selectable_value(
-                                &mut self.tab,
+                                &mut tab,
                                 Tab::Env,
                                 &self.localization.env_variables,
                             );
                             index += 1;
                         }
-                        if self.stdin.is_some() {
+                        if stdin_shown {
                             ui[index].selectable_value(
-                                &mut self.tab,
+                                &mut tab,
                                 Tab::Stdin,
                                 &self.localization.input,
                             );
+                            index += 1;
+                        }
+                        for (i, extra) in self.extra_tabs.iter().enumerate() {
+                            ui[index + i].selectable_value(&mut tab, Tab::Custom(i), extra.label());
                         }
                     });
 
                     ui.separator();
                 }
 
+                if tab != shown_tab {
+                    self.tab = tab;
+                }
+
                 // Display selected tab
-                match self.tab {
+                match shown_tab {
                     Tab::Arguments => {
-                        ui.add(&mut self.state);
+                        if self.steps.is_empty() {
+                            ui.add(&mut self.state);
+                        } else {
+                            self.update_step_forms(ui);
+                        }
+                        if self.enable_changes_summary {
+                            self.update_changes(ui);
+                        }
 
                         // Working dir
-                        if let Some((ref desc, path)) = &mut self.working_dir {
-                            if !desc.is_empty() {
+                        let compact_mode = self.compact_mode;
+                        if let Some((ref desc, path)) =
+                            self.working_dir.as_mut().filter(|_| self.show_working_dir)
+                        {
+                            if !desc.is_empty() && !compact_mode {
                                 ui.label(desc);
                             }
 
-                            let localization = self.localization;
+                            let localization = Arc::clone(&self.localization);
+                            let read_only = self.working_dir_read_only;
+                            let file_dialogs = !self.disable_file_dialogs && !read_only;
+                            let missing = &mut self.working_dir_missing;
+                            let warning_color = self.warning_color;
                             ui.horizontal(|ui| {
-                                if ui.button(&localization.select_directory).clicked() {
+                                if file_dialogs
+                                    && ui.button(&localization.select_directory).clicked()
+                                {
                                     if let Some(file) = FileDialog::new().pick_folder() {
                                         *path = file.to_string_lossy().into_owned();
+                                        *missing = false;
                                     }
                                 }
-                                ui.add(
+
+                                if *missing {
+                                    Klask::set_error_style(ui, warning_color);
+                                }
+                                let mut text = ui.add(
                                     TextEdit::singleline(path)
+                                        .interactive(!read_only)
                                         .hint_text(&localization.working_directory),
-                                )
+                                );
+                                if *missing {
+                                    ui.reset_style();
+                                    text = text.on_hover_text(&localization.working_dir_missing);
+                                }
+                                if text.changed() {
+                                    *missing = false;
+                                }
                             });
-                            ui.add_space(10.0);
+                            if !compact_mode {
+                                ui.add_space(10.0);
+                            }
                         }
                     }
                     Tab::Env => self.update_env(ui),
                     Tab::Stdin => self.update_stdin(ui),
+                    Tab::Custom(i) => self.extra_tabs[i].ui(ui),
+                }
+
+                if let Some(notes) = &mut self.notes {
+                    ui.horizontal(|ui| {
+                        ui.label(&self.localization.notes);
+                        ui.text_edit_singleline(notes);
+                        let popup_id = ui.make_persistent_id("klask_notes_history");
+                        if let Some(picked) = arg_state::ArgState::ui_history(
+                            ui,
+                            popup_id,
+                            &mut self.notes_history,
+                            &self.localization,
+                        ) {
+                            *notes = picked.concat();
+                        }
+                    });
                 }
 
                 // Run button row
                 ui.horizontal(|ui| {
-                    if ui
-                        .add_enabled(
-                            !self.is_child_running(),
-                            Button::new(&self.localization.run),
-                        )
-                        .clicked()
-                    {
+                    // Runs go into the lowest slot that isn't running
+                    let free_slot = (0..self.outputs.len()).position(|i| {
+                        !self.outputs[i].0.is_running() && !self.sequences[i].is_pending()
+                    });
+                    let subcommand_missing = self.subcommand_missing();
+                    let enabled =
+                        free_slot.is_some() && !self.stdin_too_large() && !subcommand_missing;
+                    let mut button = Button::new(&self.localization.run);
+                    if let Some(color) = self.run_button_ready_color {
+                        if enabled && self.run_ready() {
+                            button = button.fill(color);
+                        }
+                    }
+                    let run = ui.add_enabled(enabled, button).clicked();
+                    if subcommand_missing {
+                        ui.colored_label(
+                            self.warning_color,
+                            &self.localization.select_subcommand_required,
+                        );
+                    }
+
+                    if let Some(slot) = free_slot.filter(|_| run) {
+                        self.current_output = slot;
                         match self.try_start_execution(ctx.clone()) {
-                            Ok(child) => {
+                            Ok((child, sequence)) => {
                                 // Reset
-                                self.state.update_validation_error("", "");
-                                self.output = Output::new_with_child(child);
+                                self.output_expanded = false;
+                                self.state.update_validation_error(&[], "", "");
+                                for (_, state) in &mut self.steps {
+                                    state.update_validation_error(&[], "", "");
+                                }
+                                let mut next =
+                                    Output::new_with_child(child, self.slot_output_settings(slot));
+                                next.set_note(self.notes.clone());
+                                let previous = std::mem::replace(&mut self.outputs[slot].0, next);
+                                if !self.clear_output_on_run {
+                                    let separator = match self.run_separator_timestamp {
+                                        true => format!(
+                                            "\n--- {} ---\n",
+                                            output::format_timestamp(SystemTime::now())
+                                        ),
+                                        false => self.output_line_separator.clone(),
+                                    };
+                                    self.outputs[slot].0.keep_previous(previous, &separator);
+                                }
+                                if let Some(item) = &sequence.batch_item {
+                                    self.outputs[slot].0.push_text(&batch_header(item));
+                                }
+                                self.sequences[slot] = sequence;
+                                self.record_field_history();
+                                if self.usage_stats.is_some() {
+                                    let changed = self.state.get_changed_args(vec![]);
+                                    self.pending_usage.push((slot, changed));
+                                }
+
+                                if let Some(notes) = &mut self.notes {
+                                    if !self.persist_notes {
+                                        notes.clear();
+                                    }
+                                }
                             }
                             Err(err) => {
-                                if let ExecutionError::ValidationError { name, message } = &err {
-                                    self.state.update_validation_error(name, message);
+                                log!(warn, "Couldn't start the run: {err}");
+                                if let ExecutionError::ValidationError {
+                                    path,
+                                    name,
+                                    message,
+                                } = &err
+                                {
+                                    self.state.update_validation_error(path, name, message);
                                 }
-                                self.output = Output::Err(err);
+                                self.outputs[slot].0 =
+                                    Output::Err(err, self.slot_output_settings(slot));
+                                self.sequences[slot] = Sequence::default();
                             }
                         }
                     }
 
-                    if self.is_child_running() && ui.button(&self.localization.kill).clicked() {
-                        self.kill_child();
+                    if self.enable_go_to_missing {
+                        let go_to_missing = self.has_missing()
+                            && ui
+                                .button(&self.localization.go_to_missing)
+                                .on_hover_text("F8")
+                                .clicked();
+                        if go_to_missing
+                            || ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::F8))
+                        {
+                            self.focus_first_missing(ctx);
+                        }
+                    }
+
+                    if self.enable_copy_json && ui.button(&self.localization.copy_as_json).clicked()
+                    {
+                        ctx.output_mut(|o| o.copied_text = self.state.to_json());
                     }
 
-                    if self.is_child_running() {
-                        let mut running_text = String::from(&self.localization.running);
-                        for _ in 0..((2.0 * ui.input(|i| i.time)) as i32 % 4) {
-                            running_text.push('.');
+                    #[cfg(feature = "randomize")]
+                    if self.enable_randomize && ui.button(&self.localization.randomize).clicked() {
+                        let rng = &mut rand::thread_rng();
+                        self.state.randomize(rng);
+                        for (_, state) in &mut self.steps {
+                            state.randomize(rng);
                         }
-                        ui.label(running_text);
+                    }
+
+                    if self.enable_output_popout
+                        && !self.output_popped_out
+                        && ui.button(&self.localization.pop_out_output).clicked()
+                    {
+                        self.output_popped_out = true;
+                    }
+
+                    if self.can_kill_child() && ui.button(&self.localization.kill).clicked() {
+                        self.kill_child();
+                    }
+
+                    if self.enable_detach
+                        && self.can_kill_child()
+                        && ui.button(&self.localization.detach).clicked()
+                    {
+                        self.detach_children(ctx);
+                    }
+
+                    self.update_run_status(ui);
+
+                    if self.has_enough_usage_stats()
+                        && ui.small_button(&self.localization.usage_stats).clicked()
+                    {
+                        self.show_usage_stats = true;
                     }
                 });
+                self.update_shell_preview(ui);
 
-                ui.add(&mut self.output);
+                for (output, _) in &mut self.outputs {
+                    output.update();
+                    for (id, message) in output.take_field_errors() {
+                        if !self.state.set_field_error(&id, &message) {
+                            log!(
+                                warn,
+                                "The program reported an error on unknown argument {id}"
+                            );
+                        }
+                    }
+                }
+                self.update_sequences(ctx);
+                if !self.output_popped_out {
+                    self.update_output(ui);
+                }
             });
-        });
+
+        if self.output_popped_out {
+            self.update_output_viewport(ctx);
+        }
+
+        self.record_usage_stats();
+        self.update_usage_stats(ctx);
+
+        if let Some(interval) = self.repaint_interval {
+            if self.outputs.iter().any(|(output, _)| output.is_running()) {
+                ctx.request_repaint_after(interval);
+            }
+        }
     }
 }
 
-impl Klask<'_> {
+impl Klask {
+    /// Panics if the localization (after [`Settings::auto_detect_locale`]) has empty fields,
+    /// see [`Localization::validate`]
+    fn assert_localization(&self) {
+        if let Err(empty) = self.localization.validate() {
+            panic!("Localization has empty fields: {}", empty.join(", "));
+        }
+    }
+
     fn setup(&mut self, cc: &CreationContext) {
-        cc.egui_ctx.set_style(self.style.clone());
+        cc.egui_ctx.set_style(self.current_style());
 
         if let Some(custom_font) = self.custom_font.take() {
+            log!(
+                debug,
+                "Loading a custom font of {} bytes",
+                custom_font.len()
+            );
             let font_name = String::from("custom_font");
             let mut fonts = FontDefinitions::default();
 
@@ -306,11 +1040,223 @@ impl Klask<'_> {
         }
     }
 
-    fn try_start_execution(&mut self, ctx: egui::Context) -> Result<ChildApp, ExecutionError> {
-        let args = self.state.get_cmd_args(vec![])?;
+    fn current_style(&self) -> Style {
+        let style = match self.theme {
+            Some(theme) => theme.apply(self.style.clone()),
+            None => self.style.clone(),
+        };
+        match self.compact_mode {
+            true => settings::compact_style(style),
+            false => style,
+        }
+    }
+
+    fn update_view_menu(&mut self, ui: &mut Ui) {
+        let localization = Arc::clone(&self.localization);
+        let toggles = [
+            (
+                self.env.is_some(),
+                &mut self.show_env,
+                &localization.env_variables,
+            ),
+            (
+                self.stdin.is_some(),
+                &mut self.show_stdin,
+                &localization.input,
+            ),
+            (
+                self.working_dir.is_some(),
+                &mut self.show_working_dir,
+                &localization.working_directory,
+            ),
+        ];
+
+        let compact_toggle = self.enable_compact_toggle;
+        let mut compact_mode = self.compact_mode;
+        if compact_toggle || toggles.iter().any(|(enabled, ..)| *enabled) {
+            ui.menu_button(&localization.view, |ui| {
+                for (_, shown, text) in toggles.into_iter().filter(|(enabled, ..)| *enabled) {
+                    ui.checkbox(shown, text);
+                }
+                if compact_toggle {
+                    ui.separator();
+                    ui.checkbox(&mut compact_mode, &localization.compact);
+                }
+            });
+        }
+
+        if compact_mode != self.compact_mode {
+            self.compact_mode = compact_mode;
+            self.state.set_compact_mode(compact_mode);
+            for (_, state) in &mut self.steps {
+                state.set_compact_mode(compact_mode);
+            }
+            ui.ctx().set_style(self.current_style());
+        }
+    }
+
+    fn update_theme_menu(&mut self, ui: &mut Ui) {
+        let localization = Arc::clone(&self.localization);
+        if let Some(theme) = &mut self.theme {
+            let previous = *theme;
+
+            ui.menu_button(&localization.theme, |ui| {
+                for t in Theme::ALL {
+                    if ui
+                        .selectable_value(theme, t, t.name(&localization))
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            if *theme != previous {
+                ui.ctx().set_style(self.current_style());
+            }
+        }
+    }
+
+    fn update_about_menu(&self, ui: &mut Ui) {
+        let localization = Arc::clone(&self.localization);
+        if let Some(about) = &self.about {
+            ui.menu_button(&localization.about, |ui| {
+                ui.strong(&about.name);
+                if let Some(version) = &about.version {
+                    ui.label(format!("{}: {version}", localization.version));
+                }
+                if let Some(author) = &about.author {
+                    ui.label(format!("{}: {author}", localization.author));
+                }
+                if let Some(text) = &about.about {
+                    ui.separator();
+                    ui.label(text);
+                }
+            });
+        }
+    }
+
+    #[cfg(feature = "man")]
+    fn update_man_page_menu(&mut self, ui: &mut Ui) {
+        let localization = Arc::clone(&self.localization);
+        let Some(app) = &self.man_page_app else {
+            return;
+        };
+
+        ui.menu_button(&localization.man_page, |ui| {
+            if ui.button(&localization.generate_man_page).clicked() {
+                ui.close_menu();
+                let path = FileDialog::new()
+                    .add_filter("man", &["1"])
+                    .set_file_name(&format!("{}.1", app.get_name()))
+                    .save_file();
+                if let Some(path) = path {
+                    match render_man_page(app).and_then(|page| std::fs::write(&path, page)) {
+                        Ok(()) => self.man_page_saved = Some((Instant::now(), path)),
+                        Err(err) => log!(warn, "Couldn't save the man page: {err}"),
+                    }
+                }
+            }
+
+            if ui.button(&localization.copy_man_page).clicked() {
+                ui.close_menu();
+                match render_man_page(app) {
+                    Ok(page) => ui.ctx().output_mut(|o| o.copied_text = page),
+                    Err(err) => log!(warn, "Couldn't generate the man page: {err}"),
+                }
+            }
+        });
+
+        if let Some((saved, path)) = &self.man_page_saved {
+            match output::SAVED_LABEL_DURATION.checked_sub(saved.elapsed()) {
+                Some(left) => {
+                    let path = path.display().to_string();
+                    ui.label(localization.saved_to.replace("{path}", &path));
+                    ui.ctx().request_repaint_after(left);
+                }
+                None => self.man_page_saved = None,
+            }
+        }
+    }
+
+    /// Whether the main command and the steps pass validation, see
+    /// [`Settings::run_button_ready_color`]
+    fn run_ready(&mut self) -> bool {
+        let args: Vec<_> = std::iter::once(&self.state)
+            .chain(self.steps.iter().map(|(_, state)| state))
+            .map(|state| state.get_cmd_args(vec![]).ok())
+            .collect();
+        if args == self.run_ready.0 {
+            return self.run_ready.1;
+        }
+
+        let validators = &self.validators;
+        let ready = std::iter::once((&mut self.app, &self.state))
+            .chain(self.steps.iter_mut().map(|(app, state)| (app, &*state)))
+            .zip(&args)
+            .all(|((app, state), args)| {
+                args.as_ref().is_some_and(|args| {
+                    app.try_get_matches_from_mut(args.iter()).is_ok()
+                        && state.validate(validators).is_ok()
+                })
+            });
+        self.run_ready = (args, ready);
+        ready
+    }
+
+    /// Returns the child of the main command and what to run after it
+    fn try_start_execution(
+        &mut self,
+        ctx: egui::Context,
+    ) -> Result<(ChildApp, Sequence), ExecutionError> {
+        let mut args = self.state.get_cmd_args(vec![])?;
 
         // Check for validation errors
-        self.app.try_get_matches_from_mut(args.iter())?;
+        let matches = self.app.try_get_matches_from_mut(args.iter())?;
+        self.state.validate(&self.validators)?;
+
+        let mut batch = match &self.batch_arg {
+            Some(id) if self.steps.is_empty() && self.in_process.is_none() => {
+                self.state.get_batch_cmd_args(id)?
+            }
+            _ => vec![],
+        };
+        for (_, args) in &batch {
+            self.app.try_get_matches_from_mut(args.iter())?;
+        }
+
+        let mut steps = vec![];
+        for (app, state) in &mut self.steps {
+            let args = state.get_cmd_args(vec![])?;
+            match app
+                .try_get_matches_from_mut(args.iter())
+                .map_err(ExecutionError::from)
+                .and_then(|_| state.validate(&self.validators))
+            {
+                Ok(_) => steps.push(args),
+                Err(err) => {
+                    // Shown on the step, so it's not confused with an arg of the main command
+                    if let ExecutionError::ValidationError {
+                        path,
+                        name,
+                        message,
+                    } = &err
+                    {
+                        state.update_validation_error(path, name, message);
+                    }
+                    return Err(err.to_string().into());
+                }
+            }
+        }
+
+        let mut sequence = Sequence {
+            remaining: steps,
+            ..Default::default()
+        };
+
+        if let Some(f) = &self.in_process {
+            return Ok((ChildApp::run_in_process(f.clone(), matches, ctx)?, sequence));
+        }
 
         if self
             .env
@@ -325,24 +1271,632 @@ impl Klask<'_> {
                 .into());
         }
 
+        if !batch.is_empty() {
+            let (item, item_args) = batch.remove(0);
+            args = item_args;
+            sequence.batch_item = Some(item);
+            sequence.batch = batch;
+        }
+
+        let note = self.notes.as_deref().filter(|note| !note.is_empty());
+        Ok((
+            self.spawn(args, 0, self.current_output, note, ctx)?,
+            sequence,
+        ))
+    }
+
+    /// Output settings for runs in `slot`
+    fn slot_output_settings(&self, slot: usize) -> OutputSettings {
+        OutputSettings {
+            slot,
+            ..self.output_settings.clone()
+        }
+    }
+
+    /// Starts `step` of a run in the output `slot`, `note` is written to the log file
+    fn spawn(
+        &self,
+        args: Vec<String>,
+        step: usize,
+        slot: usize,
+        note: Option<&str>,
+        ctx: egui::Context,
+    ) -> Result<ChildApp, ExecutionError> {
+        // Tells the relaunched binary to run the closure
+        let mut env = self.env.clone().map(|(_, env)| env).unwrap_or_default();
+        env.push((self.child_env_var.clone(), step.to_string()));
+
+        // Whether the child can be left running when the window is closed
+        let detachable = self.enable_detach || !self.kill_on_close;
+        let log_file = self
+            .log_file
+            .as_deref()
+            .map(|path| slot_log_file(path, slot));
+
         ChildApp::run(
             args,
-            self.env.clone().map(|(_, env)| env),
+            step,
+            env,
             self.stdin.clone().map(|(_, stdin)| stdin),
             self.working_dir.clone().map(|(_, dir)| dir),
+            detachable,
+            log_file.as_deref(),
+            note,
+            self.shell_suffix.as_deref(),
             ctx,
         )
     }
 
+    /// Starts the next step of sequences whose current step finished successfully
+    fn update_sequences(&mut self, ctx: &Context) {
+        for slot in 0..self.outputs.len() {
+            if !self.sequences[slot].is_pending() {
+                continue;
+            }
+
+            // Wait until all output is read
+            let output = &mut self.outputs[slot].0;
+            if output.is_running() || output.exit_code().is_none() && !output.failed() {
+                continue;
+            }
+
+            if self.sequences[slot].batch_item.is_some() {
+                self.update_batch(slot, ctx);
+                continue;
+            }
+
+            if output.failed() {
+                self.sequences[slot].remaining.clear();
+                continue;
+            }
+
+            let args = self.sequences[slot].remaining.remove(0);
+            let step = self.sequences[slot].finished.len() + 1;
+            let note = output.note().map(str::to_string);
+            let next = match self.spawn(args, step, slot, note.as_deref(), ctx.clone()) {
+                Ok(child) => {
+                    let mut next = Output::new_with_child(child, self.slot_output_settings(slot));
+                    next.set_note(note);
+                    next
+                }
+                Err(err) => {
+                    self.sequences[slot].remaining.clear();
+                    Output::Err(err, self.slot_output_settings(slot))
+                }
+            };
+
+            let finished = std::mem::replace(&mut self.outputs[slot].0, next);
+            self.sequences[slot].finished.push(finished);
+        }
+    }
+
+    /// Shows the exit code of the finished batch run and starts the next one.
+    /// Batch runs go on after a failure, only killing stops them.
+    fn update_batch(&mut self, slot: usize, ctx: &Context) {
+        let output = &mut self.outputs[slot].0;
+        let code = output
+            .exit_code()
+            .map_or("-".into(), |code| code.to_string());
+        output.push_text(&format!("\n{}: {code}\n", self.localization.exit_code));
+
+        let sequence = &mut self.sequences[slot];
+        if sequence.batch.is_empty() {
+            sequence.batch_item = None;
+            return;
+        }
+        let (item, args) = sequence.batch.remove(0);
+        sequence.batch_item = Some(item.clone());
+
+        let note = self.outputs[slot].0.note().map(str::to_string);
+        match self.spawn(args, 0, slot, note.as_deref(), ctx.clone()) {
+            Ok(child) => {
+                let mut next = Output::new_with_child(child, self.slot_output_settings(slot));
+                next.set_note(note);
+                let previous = std::mem::replace(&mut self.outputs[slot].0, next);
+                self.outputs[slot]
+                    .0
+                    .keep_previous(previous, &batch_header(&item));
+            }
+            Err(err) => {
+                self.outputs[slot].0.push_text(&format!("{err}\n"));
+                self.sequences[slot].batch.clear();
+                self.sequences[slot].batch_item = None;
+            }
+        }
+    }
+
+    fn step_names(&self) -> Vec<String> {
+        iter::once(&self.app)
+            .chain(self.steps.iter().map(|(app, _)| app))
+            .enumerate()
+            .map(|(i, app)| format!("{}. {}", i + 1, app.get_name()))
+            .collect()
+    }
+
+    /// Collapsible summary of the args that differ from their defaults, see
+    /// [`Settings::enable_changes_summary`]
+    fn update_changes(&self, ui: &mut Ui) {
+        let changes = self.steps.iter().fold(
+            self.state.get_changed_values(vec![]),
+            |changes, (_, state)| state.get_changed_values(changes),
+        );
+
+        CollapsingHeader::new(&self.localization.changes)
+            .default_open(false)
+            .show(ui, |ui| {
+                if changes.is_empty() {
+                    ui.weak(&self.localization.no_changes);
+                }
+                for (name, value) in changes {
+                    ui.label(format!("{name}: {value}"));
+                }
+            });
+    }
+
+    fn update_step_forms(&mut self, ui: &mut Ui) {
+        let names = self.step_names();
+        let states = iter::once(&mut self.state).chain(self.steps.iter_mut().map(|(_, s)| s));
+
+        for (name, state) in names.into_iter().zip(states) {
+            CollapsingHeader::new(name)
+                .default_open(true)
+                .show(ui, |ui| ui.add(state));
+        }
+    }
+
+    fn update_step_outputs(&mut self, ui: &mut Ui) {
+        let slot = self.current_output;
+        if matches!(self.outputs[slot].0, Output::None) {
+            return;
+        }
+
+        let names = self.step_names();
+        let sequence = &mut self.sequences[slot];
+        let current = sequence.finished.len();
+        let outputs = sequence
+            .finished
+            .iter_mut()
+            .chain(iter::once(&mut self.outputs[slot].0));
+
+        for (step, (name, output)) in names.into_iter().zip(outputs).enumerate() {
+            let mut title = RichText::new(name);
+            if output.failed() {
+                title = title.color(self.error_color);
+            }
+
+            CollapsingHeader::new(title)
+                .id_source((slot, step))
+                .default_open(step == current)
+                .show(ui, |ui| ui.add(output));
+        }
+    }
+
     fn kill_child(&mut self) {
-        if let Output::Child(child, _) = &mut self.output {
+        let current = self.current_output;
+        if let Output::Child(child, ..) = &mut self.outputs[current].0 {
             child.kill();
         }
+        self.sequences[current].batch.clear();
+        self.pending_usage.retain(|(slot, _)| *slot != current);
+    }
+
+    /// Running indicator and process id during runs, otherwise the ready or finished
+    /// message when [`Settings::show_run_status`] is set
+    fn update_run_status(&mut self, ui: &mut Ui) {
+        let localization = Arc::clone(&self.localization);
+        if self.is_child_running() {
+            match self.running_indicator {
+                RunningIndicator::Dots => {
+                    let dots = (2.0 * ui.input(|i| i.time)) as usize % 4;
+                    ui.label(format!("{}{}", localization.running, ".".repeat(dots)));
+                }
+                RunningIndicator::Spinner => {
+                    ui.spinner();
+                    ui.label(&localization.running);
+                }
+                RunningIndicator::Elapsed => {
+                    // The longest of the runs
+                    let elapsed = self
+                        .outputs
+                        .iter()
+                        .filter(|(output, _)| output.is_running())
+                        .filter_map(|(output, _)| output.elapsed())
+                        .max()
+                        .unwrap_or_default();
+                    let elapsed = output::format_elapsed(elapsed);
+                    ui.label(format!("{} {elapsed}", localization.running));
+                }
+            }
+            self.update_pid(ui);
+        } else if self.show_run_status {
+            match &self.outputs[self.current_output].0 {
+                Output::None => {
+                    ui.weak(&localization.ready);
+                }
+                output if output.elapsed().is_some() => {
+                    ui.label(&localization.finished);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Process id of the running child, with a menu for sending it signals on unix
+    fn update_pid(&mut self, ui: &mut Ui) {
+        let Output::Child(child, ..) = &mut self.outputs[self.current_output].0 else {
+            return;
+        };
+        let Some(pid) = child.pid().filter(|_| child.is_running()) else {
+            return;
+        };
+        ui.weak(format!("PID {pid}"));
+
+        #[cfg(unix)]
+        ui.menu_button(&self.localization.send_signal, |ui| {
+            for (name, signal) in [
+                ("SIGINT", libc::SIGINT),
+                ("SIGTERM", libc::SIGTERM),
+                ("SIGKILL", libc::SIGKILL),
+            ] {
+                if ui.button(name).clicked() {
+                    drop(child.send_signal(signal));
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    /// Summary of the current run for [`Settings::hide_output_on_run`], shown once it exits.
+    /// Errors from starting the run are shown as usual.
+    fn update_hidden_output(&mut self, ui: &mut Ui) {
+        let output = &mut self.outputs[self.current_output].0;
+        if matches!(output, Output::Err(..)) {
+            ui.add(output);
+            return;
+        }
+        if matches!(output, Output::None)
+            || output.is_running()
+            || self.sequences[self.current_output].is_pending()
+        {
+            return;
+        }
+
+        let code = output
+            .exit_code()
+            .map_or("-".into(), |code| code.to_string());
+        ui.horizontal(|ui| {
+            ui.label(self.localization.process_exited.replace("{code}", &code));
+            if ui.button(&self.localization.expand_output).clicked() {
+                self.output_expanded = true;
+            }
+        });
+    }
+
+    /// Run tabs and the output of the current run
+    fn update_output(&mut self, ui: &mut Ui) {
+        if self.outputs.len() > 1 {
+            self.update_run_tabs(ui);
+        }
+        if self.save_output_extension.is_some() {
+            self.update_save_output(ui);
+        }
+        if self.hide_output_on_run && !self.output_expanded {
+            self.update_hidden_output(ui);
+        } else if self.steps.is_empty() {
+            ui.add(&mut self.outputs[self.current_output].0);
+        } else {
+            self.update_step_outputs(ui);
+        }
+    }
+
+    /// Shows the output in a second window, or in a floating window when the
+    /// backend can't open more. Closing it moves the output back below the form.
+    fn update_output_viewport(&mut self, ctx: &Context) {
+        let title = format!("{} - {}", self.app.get_name(), self.localization.output);
+        let builder = ViewportBuilder::default()
+            .with_title(&title)
+            .with_inner_size([600.0, 400.0]);
+
+        ctx.show_viewport_immediate(
+            ViewportId::from_hash_of("klask_output"),
+            builder,
+            |ctx, class| {
+                let show = |ui: &mut Ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.update_output(ui));
+                };
+                if class == ViewportClass::Embedded {
+                    let mut open = true;
+                    egui::Window::new(&title).open(&mut open).show(ctx, show);
+                    self.output_popped_out = open;
+                } else {
+                    egui::CentralPanel::default().show(ctx, show);
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        self.output_popped_out = false;
+                    }
+                }
+            },
+        );
+    }
+
+    fn update_save_output(&mut self, ui: &mut Ui) {
+        let localization = Arc::clone(&self.localization);
+        let extension = self.save_output_extension.as_deref().unwrap_or_default();
+        let output = &mut self.outputs[self.current_output].0;
+        let finished =
+            matches!(output, Output::Child(..) | Output::Done(..)) && !output.is_running();
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(finished, Button::new(&localization.save_output))
+                .clicked()
+            {
+                let path = FileDialog::new()
+                    .add_filter(extension, &[extension])
+                    .set_file_name(&format!("output.{extension}"))
+                    .save_file();
+                if let (Some(path), Some(text)) = (path, output.saved_text()) {
+                    match std::fs::write(&path, text) {
+                        Ok(()) => output.set_saved(path),
+                        Err(err) => log!(warn, "Couldn't save the output: {err}"),
+                    }
+                }
+            }
+
+            if let Some((path, left)) = output.recently_saved() {
+                let path = path.display().to_string();
+                ui.label(localization.saved_to.replace("{path}", &path));
+                ui.ctx().request_repaint_after(left);
+            }
+        });
+    }
+
+    fn update_run_tabs(&mut self, ui: &mut Ui) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            for (i, (_, label)) in self.outputs.iter().enumerate() {
+                ui.selectable_value(&mut self.current_output, i, label);
+            }
+        });
+
+        let output = &mut self.outputs[self.current_output].0;
+        let elapsed = output.elapsed();
+        let code = output.exit_code();
+        if elapsed.is_some() || code.is_some() {
+            ui.horizontal(|ui| {
+                if let Some(elapsed) = elapsed {
+                    ui.label(format!("⏱ {:.1} s", elapsed.as_secs_f32()));
+                }
+                if let Some(code) = code {
+                    ui.label(format!("{}: {code}", self.localization.exit_code));
+                }
+            });
+        }
+    }
+
+    fn record_usage_stats(&mut self) {
+        if let Some((stats, path)) = &mut self.usage_stats {
+            let outputs = &mut self.outputs;
+            self.pending_usage.retain(|(slot, changed)| {
+                let Output::Child(child, ..) = &mut outputs[*slot].0 else {
+                    return false;
+                };
+                match child.exit_status() {
+                    Some(status) => {
+                        if status.success() {
+                            stats.record(changed);
+                            // Stats are best effort, don't bother the user with io errors
+                            drop(stats.save(path));
+                        }
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+    }
+
+    /// Adds the values of this run to the history of the fields and saves it
+    fn record_field_history(&mut self) {
+        self.state.record_history();
+        for (_, state) in &mut self.steps {
+            state.record_history();
+        }
+        if let Some(notes) = self.notes.as_ref().filter(|notes| !notes.is_empty()) {
+            field_history::push(&mut self.notes_history, vec![notes.clone()]);
+        }
+
+        if let Some((loaded, path)) = &self.field_history {
+            let mut history = FieldHistory::clone(loaded);
+            self.state.collect_history(&mut history);
+            for (_, state) in &self.steps {
+                state.collect_history(&mut history);
+            }
+            match self.notes_history.is_empty() {
+                true => history.fields.remove(NOTES_KEY),
+                false => history
+                    .fields
+                    .insert(NOTES_KEY.into(), self.notes_history.clone()),
+            };
+            // History is best effort like usage stats, don't bother the user with io errors
+            drop(history.save(path));
+        }
+    }
+
+    fn has_enough_usage_stats(&self) -> bool {
+        matches!(&self.usage_stats, Some((stats, _)) if stats.runs >= usage_stats::MIN_RUNS)
+    }
+
+    fn update_usage_stats(&mut self, ctx: &Context) {
+        if let Some((stats, _)) = &self.usage_stats {
+            egui::Window::new(&self.localization.usage_stats)
+                .open(&mut self.show_usage_stats)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    let mut args: Vec<_> = stats.args.iter().collect();
+                    args.sort_by(|a, b| b.1.cmp(a.1));
+
+                    let max = args.first().map_or(1, |(_, count)| **count).max(1);
+                    for (id, count) in args {
+                        ui.add(
+                            ProgressBar::new(*count as f32 / max as f32)
+                                .text(format!("{id}: {count}/{}", stats.runs)),
+                        );
+                    }
+                });
+        }
+    }
+
+    fn update_close_confirmation(&mut self, ctx: &Context) {
+        let running = self.outputs.iter().any(|(output, _)| output.is_running());
+        let close_requested = ctx.input(|i| i.viewport().close_requested());
+        if self.confirm_close && running && close_requested {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.show_close_dialog = true;
+        } else if close_requested {
+            self.on_close();
+        }
+
+        if !self.show_close_dialog {
+            return;
+        }
+
+        // Only processes started to be detachable can outlive the GUI
+        let can_keep_running = self.outputs.iter().any(|(output, _)| {
+            matches!(output, Output::Child(child, ..) if child.is_running() && child.is_detachable())
+        });
+
+        let localization = Arc::clone(&self.localization);
+        let mut kill = None;
+        egui::Window::new(&localization.confirm_close_while_running)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(&localization.close_while_running);
+                ui.horizontal(|ui| {
+                    if ui.button(&localization.kill_and_close).clicked() {
+                        kill = Some(true);
+                    }
+                    if can_keep_running && ui.button(&localization.keep_running_and_close).clicked()
+                    {
+                        kill = Some(false);
+                    }
+                    if ui.button(&localization.cancel).clicked() {
+                        self.show_close_dialog = false;
+                    }
+                });
+            });
+
+        if let Some(kill) = kill {
+            for (output, _) in &mut self.outputs {
+                if let Output::Child(child, ..) = output {
+                    if kill {
+                        child.kill();
+                    }
+                }
+                output.detach();
+            }
+            self.show_close_dialog = false;
+            ctx.send_viewport_cmd(ViewportCommand::Close);
+        }
+    }
+
+    /// Line the main command is started with, see [`Settings::shell_suffix`]
+    fn update_shell_preview(&self, ui: &mut Ui) {
+        let Some(suffix) = self.shell_suffix.as_deref() else {
+            return;
+        };
+        if self.in_process.is_some() {
+            return;
+        }
+
+        if let Ok(args) = self.state.get_cmd_args(vec![]) {
+            let exe = std::env::current_exe()
+                .map(|exe| exe.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let line = child_app::shell_preview(&child_app::shell_line(&exe, &args, suffix));
+            ui.label(RichText::new(line).monospace().weak());
+        }
+    }
+
+    /// Help of the focused arg, see [`Settings::show_help_panel`]
+    fn update_help_panel(&self, ui: &mut Ui) {
+        let localization = Arc::clone(&self.localization);
+        ui.heading(&localization.help_panel);
+        ui.separator();
+
+        let forms = iter::once(&self.state).chain(self.steps.iter().map(|(_, state)| state));
+        let focused = forms
+            .filter_map(AppState::focused_arg)
+            .filter_map(|arg| Some((arg.focused_at?, arg)))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        egui::ScrollArea::vertical().show(ui, |ui| match focused {
+            Some((_, arg)) => {
+                ui.strong(&arg.name);
+                if let Some(desc) = &arg.desc {
+                    ui.label(desc);
+                }
+            }
+            None => {
+                ui.weak(&localization.help_panel_empty);
+            }
+        });
+    }
+
+    /// Kills or detaches the running children per [`Settings::kill_on_close`],
+    /// then calls [`Settings::on_window_close`]
+    fn on_close(&mut self) {
+        for (output, _) in &mut self.outputs {
+            match output {
+                Output::Child(child, ..) if self.kill_on_close => child.kill(),
+                output => output.detach(),
+            }
+        }
+
+        if let Some(hook) = &self.on_window_close {
+            hook.call();
+        }
+    }
+
+    /// Whether the main form or a step has a required arg without a value
+    fn has_missing(&mut self) -> bool {
+        self.state.has_missing() || self.steps.iter_mut().any(|(_, state)| state.has_missing())
+    }
+
+    /// Whether the main form or a step requires a subcommand that isn't selected
+    fn subcommand_missing(&self) -> bool {
+        self.state.subcommand_missing()
+            || self
+                .steps
+                .iter()
+                .any(|(_, state)| state.subcommand_missing())
+    }
+
+    /// Shows the arguments and scrolls to the first required arg without a value
+    fn focus_first_missing(&mut self, ctx: &Context) {
+        let mut forms =
+            std::iter::once(&mut self.state).chain(self.steps.iter_mut().map(|(_, state)| state));
+        if forms.any(AppState::focus_first_missing) {
+            self.tab = Tab::Arguments;
+            ctx.request_repaint();
+        }
+    }
+
+    /// Lets every running child continue in the background and closes the window
+    fn detach_children(&mut self, ctx: &Context) {
+        for (output, _) in &mut self.outputs {
+            output.detach();
+        }
+        ctx.send_viewport_cmd(ViewportCommand::Close);
     }
 
     fn is_child_running(&self) -> bool {
-        match &self.output {
-            Output::Child(child, _) => child.is_running(),
+        self.outputs[self.current_output].0.is_running()
+    }
+
+    fn can_kill_child(&self) -> bool {
+        match &self.outputs[self.current_output].0 {
+            Output::Child(child, ..) => child.is_running() && child.can_kill(),
             _ => false,
         }
     }
@@ -350,7 +1904,7 @@ impl Klask<'_> {
     fn update_env(&mut self, ui: &mut Ui) {
         let (ref desc, env) = self.env.as_mut().unwrap();
 
-        if !desc.is_empty() {
+        if !desc.is_empty() && !self.compact_mode {
             ui.label(desc);
         }
 
@@ -371,13 +1925,27 @@ impl Klask<'_> {
                             }
 
                             if key.is_empty() {
-                                Klask::set_error_style(ui);
+                                Klask::set_error_style(ui, self.error_color);
                             }
 
                             ui.text_edit_singleline(key);
 
                             if key.is_empty() {
                                 ui.reset_style();
+                            } else if !self.known_env_vars.is_empty() {
+                                let localization = Arc::clone(&self.localization);
+                                match self.known_env_vars.get(key.as_str()) {
+                                    Some(Some(arg)) => ui.label("✔").on_hover_text(format!(
+                                        "{} ({arg})",
+                                        localization.env_var_known
+                                    )),
+                                    Some(None) => {
+                                        ui.label("✔").on_hover_text(&localization.env_var_known)
+                                    }
+                                    None => ui
+                                        .colored_label(self.warning_color, "?")
+                                        .on_hover_text(&localization.env_var_unknown),
+                                };
                             }
                         });
 
@@ -395,6 +1963,10 @@ impl Klask<'_> {
             }
         }
 
+        for extra_ui in &self.env_extra_ui {
+            extra_ui.ui(ui, env);
+        }
+
         if ui.button(&self.localization.new_value).clicked() {
             env.push(Default::default());
         }
@@ -403,14 +1975,27 @@ impl Klask<'_> {
     }
 
     fn update_stdin(&mut self, ui: &mut Ui) {
+        let output = &self.outputs[self.current_output].0;
         let (ref desc, stdin) = self.stdin.as_mut().unwrap();
 
-        if !desc.is_empty() {
+        if !desc.is_empty() && !self.compact_mode {
             ui.label(desc);
         }
 
         let localization = &self.localization;
 
+        // For chaining runs, like feeding the result of one into a filter
+        let finished =
+            matches!(output, Output::Child(..) | Output::Done(..)) && !output.is_running();
+        if ui
+            .add_enabled(finished, Button::new(&localization.stdin_from_output))
+            .clicked()
+        {
+            if let Some(text) = output.text() {
+                *stdin = StdinType::Text(text);
+            }
+        }
+
         ui.columns(2, |ui| {
             if ui[0]
                 .selectable_label(matches!(stdin, StdinType::Text(_)), &localization.text)
@@ -431,7 +2016,8 @@ impl Klask<'_> {
         match stdin {
             StdinType::File(path) => {
                 ui.horizontal(|ui| {
-                    if ui.button(&localization.select_file).clicked() {
+                    if !self.disable_file_dialogs && ui.button(&localization.select_file).clicked()
+                    {
                         if let Some(file) = FileDialog::new().pick_file() {
                             *path = file.to_string_lossy().into_owned();
                         }
@@ -440,20 +2026,69 @@ impl Klask<'_> {
                 });
             }
             StdinType::Text(text) => {
-                ui.text_edit_multiline(text);
+                let placeholder = self.stdin_placeholder.as_ref();
+                ui.add(
+                    TextEdit::multiline(text)
+                        .hint_text(placeholder.unwrap_or(&localization.stdin_placeholder)),
+                );
+                if let Some(placeholder) = placeholder {
+                    ui.weak(placeholder);
+                }
             }
         };
+
+        if let Some(max) = self.stdin_max_bytes {
+            let current = stdin_len(stdin);
+            if current > max as u64 {
+                ui.colored_label(
+                    self.error_color,
+                    localization
+                        .stdin_size_warning
+                        .replace("{current}", &current.to_string())
+                        .replace("{max}", &max.to_string()),
+                );
+            } else {
+                ui.weak(format!("{current} / {max}"));
+            }
+        }
+
+        let (StdinType::File(text) | StdinType::Text(text)) = stdin;
+        for extra_ui in &self.stdin_extra_ui {
+            extra_ui.ui(ui, text);
+        }
+    }
+
+    fn stdin_too_large(&self) -> bool {
+        match (&self.stdin, self.stdin_max_bytes) {
+            (Some((_, stdin)), Some(max)) => stdin_len(stdin) > max as u64,
+            _ => false,
+        }
     }
 
-    fn set_error_style(ui: &mut Ui) {
+    fn set_error_style(ui: &mut Ui, color: Color32) {
         let style = ui.style_mut();
-        style.visuals.widgets.inactive.bg_stroke.color = Color32::RED;
-        style.visuals.widgets.inactive.bg_stroke.width = 1.0;
-        style.visuals.widgets.hovered.bg_stroke.color = Color32::RED;
-        style.visuals.widgets.active.bg_stroke.color = Color32::RED;
-        style.visuals.widgets.open.bg_stroke.color = Color32::RED;
-        style.visuals.widgets.noninteractive.bg_stroke.color = Color32::RED;
-        style.visuals.selection.stroke.color = Color32::RED;
+        // Themes with thicker outlines (like high contrast) get thicker error outlines
+        let width = style.visuals.widgets.inactive.bg_stroke.width.max(1.0);
+        style.visuals.widgets.inactive.bg_stroke.color = color;
+        style.visuals.widgets.inactive.bg_stroke.width = width;
+        style.visuals.widgets.hovered.bg_stroke.color = color;
+        style.visuals.widgets.active.bg_stroke.color = color;
+        style.visuals.widgets.open.bg_stroke.color = color;
+        style.visuals.widgets.noninteractive.bg_stroke.color = color;
+        style.visuals.selection.stroke.color = color;
+    }
+}
+
+/// Environment variables of args set with [`clap::Arg::env`], including in subcommands
+fn arg_env_vars(app: &Command, vars: &mut BTreeMap<String, Option<String>>) {
+    for arg in app.get_arguments() {
+        if let Some(env) = arg.get_env() {
+            let name = to_sentence_case(arg.get_id().as_str());
+            vars.insert(env.to_string_lossy().into_owned(), Some(name));
+        }
+    }
+    for subcommand in app.get_subcommands() {
+        arg_env_vars(subcommand, vars);
     }
 }
 
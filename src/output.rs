@@ -1,11 +1,23 @@
 use crate::child_app::ChildApp;
 use crate::error::ExecutionError;
+use crate::settings::{Localization, ScrollBehavior};
 use cansi::{v3::CategorisedSlice, Color, Intensity};
-use eframe::egui::{vec2, Color32, Label, ProgressBar, RichText, Ui, Widget};
+use eframe::egui::{
+    self, vec2, Align, Color32, Label, ProgressBar, Rect, RichText, TextEdit, Ui, Vec2, Widget,
+};
+use flate2::{read::MultiGzDecoder, write::GzEncoder, Compression};
 use linkify::{LinkFinder, LinkKind};
-use std::collections::hash_map::DefaultHasher;
+use std::collections::{hash_map::DefaultHasher, HashMap};
 use std::hash::{Hash, Hasher};
-use std::io::Write;
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    mpsc::{self, Sender},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Displays a progress bar in the output. First call creates
 /// a progress bar and future calls update it.
@@ -55,16 +67,714 @@ pub fn progress_bar_with_id(id: impl Hash, description: &str, value: f32) {
     OutputType::ProgressBar(description.to_string(), value).send(h.finish());
 }
 
+/// Start of a printed line that sets the progress bar of the output instead of being
+/// shown. It's followed by the progress between 0 and 1 and optionally a colon and a
+/// description, so programs in any language can print `KLASK_PROGRESS:0.75:Processing...`.
+/// All such lines update the same progress bar, use [`progress_bar_with_id`] for more.
+pub const PROGRESS_PREFIX: &str = "KLASK_PROGRESS:";
+
+/// Id of the progress bar set with [`PROGRESS_PREFIX`] lines. Ids of other progress
+/// bars are hashes, and 0 is text.
+const PROGRESS_LINE_ID: u64 = u64::MAX;
+
+/// Sets the progress bar of the output by printing a [`PROGRESS_PREFIX`] line.
+/// Fraction is a f32 between 0 and 1.
+/// ```no_run
+/// # use clap::Command;
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(Command::new("Example"), Settings::default(), |matches| {
+///         for i in 0..=100 {
+///             klask::output::set_progress(i as f32 / 100.0, Some("Processing..."));
+///         }
+///     });
+/// }
+/// ```
+pub fn set_progress(fraction: f32, label: Option<&str>) {
+    match label {
+        Some(label) => println!("{PROGRESS_PREFIX}{fraction}:{}", label.replace('\n', " ")),
+        None => println!("{PROGRESS_PREFIX}{fraction}"),
+    }
+}
+
+/// Start of a printed line that shows an error on an argument of the form instead
+/// of in the output, when [`crate::Settings::enable_field_errors`] is set. It's
+/// followed by the argument id, a colon and the message, so programs in any
+/// language can print `KLASK_FIELD_ERROR:output:File already exists`.
+pub const FIELD_ERROR_PREFIX: &str = "KLASK_FIELD_ERROR:";
+
+/// Shows `message` as a validation error on the argument with the id `id`, by
+/// printing a [`FIELD_ERROR_PREFIX`] line. The error is cleared on the next run.
+/// ```no_run
+/// # use clap::{Arg, Command};
+/// # use klask::Settings;
+/// fn main() {
+///     let app = Command::new("Example").arg(Arg::new("output").long("output"));
+///     let mut settings = Settings::default();
+///     settings.enable_field_errors = true;
+///     klask::run_app(app, settings, |matches| {
+///         klask::output::field_error("output", "File already exists");
+///     });
+/// }
+/// ```
+pub fn field_error(id: &str, message: &str) {
+    println!("{FIELD_ERROR_PREFIX}{id}:{}", message.replace('\n', " "));
+}
+
+/// Removes ANSI escape sequences (like colors) from the text.
+/// ```
+/// assert_eq!(klask::output::strip_ansi("\x1b[1;31mError\x1b[0m"), "Error");
+/// ```
+pub fn strip_ansi(s: &str) -> String {
+    enum State {
+        Text,
+        Escape,
+        /// `ESC [` until a final byte
+        Csi,
+        /// `ESC ]` until `BEL` or `ESC \`
+        Osc,
+        OscEscape,
+    }
+
+    let mut result = String::with_capacity(s.len());
+    let mut state = State::Text;
+
+    for c in s.chars() {
+        state = match (state, c) {
+            (State::Text, '\x1b') => State::Escape,
+            (State::Text, c) => {
+                result.push(c);
+                State::Text
+            }
+            (State::Escape, '[') => State::Csi,
+            (State::Escape, ']') => State::Osc,
+            // Other escapes are two characters long
+            (State::Escape, _) => State::Text,
+            (State::Csi, '\x40'..='\x7e') => State::Text,
+            (State::Csi, _) => State::Csi,
+            (State::Osc | State::OscEscape, '\x07') => State::Text,
+            (State::Osc | State::OscEscape, '\x1b') => State::OscEscape,
+            (State::OscEscape, '\\') => State::Text,
+            (State::Osc | State::OscEscape, _) => State::Osc,
+        };
+    }
+
+    result
+}
+
+/// Turns output of the child into text line by line. Invalid UTF-8 is replaced with
+/// `�`, or with `binary_as_hex` the whole line is shown as hex bytes.
+pub(crate) fn decode_output(bytes: &[u8], binary_as_hex: bool) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    for line in bytes.split_inclusive(|&b| b == b'\n') {
+        match std::str::from_utf8(line) {
+            Ok(line) => text.push_str(line),
+            Err(_) if binary_as_hex => {
+                let (line, newline) = match line.strip_suffix(b"\n") {
+                    Some(line) => (line, true),
+                    None => (line, false),
+                };
+                let hex: Vec<String> = line.iter().map(|b| format!("{b:02x}")).collect();
+                text.push_str(&hex.join(" "));
+                if newline {
+                    text.push('\n');
+                }
+            }
+            Err(_) => text.push_str(&String::from_utf8_lossy(line)),
+        }
+    }
+    text
+}
+
+/// Formats the time as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub(crate) fn format_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Duration as minutes and seconds like `02:05`, with hours when it's that long
+pub(crate) fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs / 3600 {
+        0 => format!("{:02}:{:02}", secs / 60, secs % 60),
+        hours => format!("{hours}:{:02}:{:02}", secs / 60 % 60, secs % 60),
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum Output {
     None,
-    Err(ExecutionError),
-    Child(ChildApp, Vec<(u64, OutputType)>),
+    Err(ExecutionError, OutputSettings),
+    Child(ChildApp, Vec<(u64, OutputType)>, OutputState),
+    /// A process that finished outside of klask, with its exit code
+    Done(Vec<(u64, OutputType)>, OutputState, Option<i32>),
 }
 
 impl Output {
-    pub fn new_with_child(child: ChildApp) -> Self {
-        Self::Child(child, vec![])
+    pub fn new_with_child(child: ChildApp, settings: OutputSettings) -> Self {
+        Self::Child(child, vec![], OutputState::new(settings))
+    }
+
+    /// Shows a process that was already run to completion like a finished child.
+    /// Stdout comes before stderr, invalid UTF-8 is replaced.
+    pub fn from_completed_process(process: std::process::Output) -> Self {
+        let mut output = vec![];
+        let mut state = OutputState::new(OutputSettings {
+            strip_ansi: true,
+            ..Default::default()
+        });
+        for bytes in [&process.stdout, &process.stderr] {
+            parse_output(&String::from_utf8_lossy(bytes), &mut output, &mut state);
+        }
+        Self::Done(output, state, process.status.code())
+    }
+
+    /// Puts the output of a previous run before this one, divided by `separator`.
+    /// Nothing is kept if the previous run printed nothing.
+    pub fn keep_previous(&mut self, previous: Output, separator: &str) {
+        let (
+            Output::Child(_, output, state),
+            Output::Child(
+                _,
+                mut previous,
+                OutputState {
+                    compressed,
+                    lines_seen,
+                    ..
+                },
+            ),
+        ) = (self, previous)
+        else {
+            return;
+        };
+        if previous.is_empty() && lines_seen == 0 {
+            return;
+        }
+
+        // Progress bars of the previous run can't be updated anymore
+        for (id, _) in &mut previous {
+            *id = 0;
+        }
+        previous.push((0, OutputType::Text(separator.to_string())));
+        previous.append(output);
+        *output = previous;
+
+        if compressed.is_some() {
+            state.compressed = compressed;
+        }
+    }
+
+    /// Adds text after the output so far, for messages that aren't from the child.
+    pub fn push_text(&mut self, text: &str) {
+        if let Output::Child(_, output, _) = self {
+            output.push((0, OutputType::Text(text.to_string())));
+        }
+    }
+
+    /// Reads new output from the child. Should be called every frame, even when the output isn't shown.
+    pub fn update(&mut self) {
+        if let Output::Child(child, output, state) = self {
+            let read = decode_output(&child.read(), state.settings.binary_as_hex);
+            state.new_output = !read.is_empty();
+            parse_output(&read, output, state);
+
+            if let Some(compressed) = &mut state.compressed {
+                compressed.compress_old(output);
+            }
+
+            if state.elapsed.is_none() && !child.is_running() {
+                state.elapsed = Some(state.started.elapsed());
+                log!(
+                    debug,
+                    "Output of the child ended, exit status {:?}",
+                    child.exit_status()
+                );
+            }
+        }
+    }
+
+    /// Errors the child printed for arguments since the last call, as ids and messages.
+    /// See [`FIELD_ERROR_PREFIX`].
+    pub fn take_field_errors(&mut self) -> Vec<(String, String)> {
+        match self {
+            Output::Child(_, _, state) => std::mem::take(&mut state.field_errors),
+            _ => vec![],
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        match self {
+            Output::Child(child, ..) => child.is_running(),
+            _ => false,
+        }
+    }
+
+    /// Printed text of a finished run, including compressed output but no progress bars.
+    /// ANSI codes are removed with [`OutputSettings::strip_ansi`].
+    pub fn text(&self) -> Option<String> {
+        let (output, state) = match self {
+            Output::Child(child, output, state) if !child.is_running() => (output, state),
+            Output::Done(output, state, _) => (output, state),
+            _ => return None,
+        };
+
+        let text = printed_text(output, state);
+        Some(match state.settings.strip_ansi {
+            true => strip_ansi(&text),
+            false => text,
+        })
+    }
+
+    /// Lets a running child keep running in the background, see [`ChildApp::detach`].
+    pub fn detach(&mut self) {
+        if let Output::Child(child, ..) = self {
+            if child.is_running() {
+                child.detach();
+            }
+        }
+    }
+
+    /// Run notes of this run, see [`crate::Settings::enable_run_notes`]
+    pub fn note(&self) -> Option<&str> {
+        match self {
+            Output::Child(.., state) | Output::Done(_, state, _) => state.note.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Empty notes are left out
+    pub fn set_note(&mut self, note: Option<String>) {
+        if let Output::Child(.., state) | Output::Done(_, state, _) = self {
+            state.note = note.filter(|note| !note.is_empty());
+        }
+    }
+
+    /// [`Output::text`] for saving to a file, starting with a `# Note:` line for the notes
+    pub fn saved_text(&self) -> Option<String> {
+        let text = self.text()?;
+        Some(match self.note() {
+            Some(note) => format!("{}{text}", note_line(note)),
+            None => text,
+        })
+    }
+
+    /// Remembers that the output was saved, for showing the path for a few seconds.
+    pub fn set_saved(&mut self, path: PathBuf) {
+        if let Output::Child(.., state) | Output::Done(_, state, _) = self {
+            state.save_timestamp = Some((Instant::now(), path));
+        }
+    }
+
+    /// Path the output was saved to in the last few seconds, with how long it's still shown.
+    pub fn recently_saved(&self) -> Option<(&Path, Duration)> {
+        let (Output::Child(.., state) | Output::Done(_, state, _)) = self else {
+            return None;
+        };
+        let (saved, path) = state.save_timestamp.as_ref()?;
+        let left = SAVED_LABEL_DURATION.checked_sub(saved.elapsed())?;
+        Some((path, left))
+    }
+
+    /// Time since the child was started, stops counting once it's finished.
+    pub fn elapsed(&self) -> Option<Duration> {
+        match self {
+            Output::Child(_, _, state) => Some(state.elapsed.unwrap_or(state.started.elapsed())),
+            _ => None,
+        }
+    }
+
+    /// Whether it couldn't start or exited with an error
+    pub fn failed(&mut self) -> bool {
+        match self {
+            Output::None => false,
+            Output::Err(..) => true,
+            Output::Child(child, ..) => child.exit_status().is_some_and(|s| !s.success()),
+            Output::Done(.., code) => *code != Some(0),
+        }
+    }
+
+    /// None while running or when killed by a signal
+    pub fn exit_code(&mut self) -> Option<i32> {
+        match self {
+            Output::Child(child, ..) => child.exit_status()?.code(),
+            Output::Done(.., code) => *code,
+            _ => None,
+        }
+    }
+}
+
+/// Comment line with the run notes put before saved and logged output
+pub(crate) fn note_line(note: &str) -> String {
+    format!("# Note: {}\n", note.replace('\n', " "))
+}
+
+/// Text of the output including compressed output, without progress bars
+fn printed_text(output: &[(u64, OutputType)], state: &OutputState) -> String {
+    let mut text = state
+        .compressed
+        .as_ref()
+        .map(CompressedOutput::decompress)
+        .unwrap_or_default();
+    for (_, o) in output {
+        if let OutputType::Text(t) = o {
+            text.push_str(t);
+        }
+    }
+    text
+}
+
+/// Adds text read from the child, updating progress bars it sent.
+fn parse_output(str: &str, output: &mut Vec<(u64, OutputType)>, state: &mut OutputState) {
+    let mut iter = str.split(MAGIC);
+
+    if let Some(text) = iter.next() {
+        push_printed(text, output, state);
+    }
+
+    while let Some(id) = iter.next() {
+        if let Ok(id) = id.parse() {
+            if let Some(new) = OutputType::parse(&mut iter) {
+                update_progress_bar(output, id, new);
+            }
+        }
+
+        if let Some(text) = iter.next() {
+            // Get rid of the newline
+            push_printed(&text[1..], output, state);
+        }
+    }
+}
+
+/// Replaces the progress bar with the id, or adds it after the output if it's new
+fn update_progress_bar(output: &mut Vec<(u64, OutputType)>, id: u64, new: OutputType) {
+    if let Some((_, exists)) = output.iter_mut().find(|(i, _)| *i == id) {
+        *exists = new;
+    } else {
+        output.push((id, new));
+    }
+}
+
+/// Adds printed text, updating the progress bar for [`PROGRESS_PREFIX`] lines and
+/// leaving out the field error lines when they are enabled
+fn push_printed(text: &str, output: &mut Vec<(u64, OutputType)>, state: &mut OutputState) {
+    let text = match state.settings.field_errors {
+        true => take_field_errors(text, &mut state.field_errors),
+        false => text.to_string(),
+    };
+
+    let mut push_text = |text: &mut String, output: &mut Vec<(u64, OutputType)>| {
+        if !text.is_empty() {
+            state.lines_seen += text.matches('\n').count();
+            output.push((0, OutputType::Text(std::mem::take(text))));
+        }
+    };
+
+    let mut kept = String::new();
+    for line in text.split_inclusive('\n') {
+        match parse_progress_line(line) {
+            Some(progress_bar) => {
+                push_text(&mut kept, output);
+                update_progress_bar(output, PROGRESS_LINE_ID, progress_bar);
+            }
+            None => kept.push_str(line),
+        }
+    }
+    push_text(&mut kept, output);
+}
+
+/// Progress bar of a [`PROGRESS_PREFIX`] line, None for other lines
+fn parse_progress_line(line: &str) -> Option<OutputType> {
+    let rest = line
+        .strip_prefix(PROGRESS_PREFIX)?
+        .trim_end_matches(['\r', '\n']);
+    let (fraction, label) = rest.split_once(':').unwrap_or((rest, ""));
+    let fraction: f32 = fraction.trim().parse().ok()?;
+    // Ends with a newline like the other progress bars, for copying out text
+    Some(OutputType::ProgressBar(
+        format!("{label}\n"),
+        fraction.clamp(0.0, 1.0),
+    ))
+}
+
+/// Moves the [`FIELD_ERROR_PREFIX`] lines of `text` into `errors`, returns the rest.
+/// Lines are expected to be read whole, like the ones printed with `println!`.
+fn take_field_errors(text: &str, errors: &mut Vec<(String, String)>) -> String {
+    let mut kept = String::new();
+    for line in text.split_inclusive('\n') {
+        let error = line
+            .strip_prefix(FIELD_ERROR_PREFIX)
+            .and_then(|rest| rest.trim_end_matches(['\r', '\n']).split_once(':'));
+        match error {
+            Some((id, message)) => errors.push((id.to_string(), message.to_string())),
+            None => kept.push_str(line),
+        }
+    }
+    kept
+}
+
+/// Output options from [`crate::Settings`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OutputSettings {
+    /// Move output older than that many lines into a compressed buffer
+    pub compress_after: Option<usize>,
+    /// Show a progress bar based on the number of lines
+    pub expected_lines: Option<usize>,
+    /// Remove ANSI escape codes from copied output
+    pub strip_ansi: bool,
+    /// Show lines that aren't valid UTF-8 as hex bytes
+    pub binary_as_hex: bool,
+    /// Where to scroll when new output arrives
+    pub scroll_behavior: ScrollBehavior,
+    /// Take [`FIELD_ERROR_PREFIX`] lines out of the output
+    pub field_errors: bool,
+    /// Some when [`crate::Settings::output_color_map`] isn't empty
+    pub keyword_colors: Option<Arc<KeywordColors>>,
+    /// From [`crate::Settings::error_color`]
+    pub error_color: Color32,
+    /// Run slot the output is shown in, keeps the ids of outputs shown at once apart
+    pub slot: usize,
+    /// Text of the buttons and labels
+    pub localization: Arc<Localization>,
+}
+
+/// Keys of [`crate::Settings::output_color_map`], prepared for matching
+#[derive(Debug)]
+pub(crate) struct KeywordColors {
+    keywords: Vec<(Keyword, Color32)>,
+    case_sensitive: bool,
+}
+
+#[derive(Debug)]
+enum Keyword {
+    /// Lowercase when not case sensitive
+    Literal(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl KeywordColors {
+    pub fn new(map: HashMap<String, Color32>, case_sensitive: bool) -> Self {
+        let mut map: Vec<_> = map.into_iter().filter(|(key, _)| !key.is_empty()).collect();
+        // Keywords matching at the same place are picked the same way every run
+        map.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let keywords = map
+            .into_iter()
+            .map(|(key, color)| (Keyword::new(key, case_sensitive), color))
+            .collect();
+        Self {
+            keywords,
+            case_sensitive,
+        }
+    }
+
+    /// Splits `text` into parts, with the color of the keyword for the ones that match.
+    /// The leftmost match wins, and the longest of the ones starting there.
+    pub fn split<'t>(&self, text: &'t str) -> Vec<(&'t str, Option<Color32>)> {
+        // ASCII only, so the byte offsets stay the same
+        let folded;
+        let haystack = match self.case_sensitive {
+            true => text,
+            false => {
+                folded = text.to_ascii_lowercase();
+                &folded
+            }
+        };
+
+        let mut parts = vec![];
+        let mut pos = 0;
+        while let Some((range, color)) = self
+            .keywords
+            .iter()
+            .filter_map(|(keyword, color)| Some((keyword.find(text, haystack, pos)?, *color)))
+            .min_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)))
+        {
+            if range.start > pos {
+                parts.push((&text[pos..range.start], None));
+            }
+            parts.push((&text[range.clone()], Some(color)));
+            pos = range.end;
+        }
+        if pos < text.len() {
+            parts.push((&text[pos..], None));
+        }
+        parts
+    }
+}
+
+impl Keyword {
+    fn new(mut key: String, case_sensitive: bool) -> Self {
+        #[cfg(feature = "regex")]
+        if let Ok(regex) = regex::RegexBuilder::new(&key)
+            .case_insensitive(!case_sensitive)
+            .build()
+        {
+            return Self::Regex(regex);
+        }
+
+        if !case_sensitive {
+            key.make_ascii_lowercase();
+        }
+        Self::Literal(key)
+    }
+
+    /// First non-empty match at or after `pos`. Literals are searched in `folded`, the
+    /// lowercase `text` when not case sensitive.
+    #[cfg_attr(not(feature = "regex"), allow(unused_variables))]
+    fn find(&self, text: &str, folded: &str, pos: usize) -> Option<Range<usize>> {
+        match self {
+            Self::Literal(key) => {
+                let start = pos + folded[pos..].find(key.as_str())?;
+                Some(start..start + key.len())
+            }
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => {
+                let mut at = pos;
+                loop {
+                    let found = regex.find_at(text, at)?;
+                    if !found.is_empty() {
+                        return Some(found.range());
+                    }
+                    // Skip the empty match, staying on a char boundary
+                    at = found.start() + text[found.start()..].chars().next()?.len_utf8();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct OutputState {
+    settings: OutputSettings,
+    compressed: Option<CompressedOutput>,
+    lines_seen: usize,
+    /// Something was read in the last update, for scrolling to it
+    new_output: bool,
+    started: Instant,
+    /// Set once the child is finished
+    elapsed: Option<Duration>,
+    /// When and where the output was last saved to a file
+    save_timestamp: Option<(Instant, PathBuf)>,
+    /// Read with [`Output::take_field_errors`]
+    field_errors: Vec<(String, String)>,
+    /// Run notes the run was started with, see [`crate::Settings::enable_run_notes`]
+    note: Option<String>,
+}
+
+impl OutputState {
+    fn new(settings: OutputSettings) -> Self {
+        Self {
+            compressed: settings.compress_after.map(CompressedOutput::new),
+            settings,
+            lines_seen: 0,
+            new_output: false,
+            started: Instant::now(),
+            elapsed: None,
+            save_timestamp: None,
+            field_errors: vec![],
+            note: None,
+        }
+    }
+}
+
+/// How long the path is shown after saving the output
+pub(crate) const SAVED_LABEL_DURATION: Duration = Duration::from_secs(3);
+
+/// Output that was moved out of the main buffer. Every chunk is compressed
+/// on a background thread as a separate gzip member.
+#[derive(Debug)]
+pub(crate) struct CompressedOutput {
+    max_lines: usize,
+    /// Number of lines moved out so far, for numbering the rest
+    lines: usize,
+    sender: Sender<String>,
+    compressed: Arc<Mutex<Vec<u8>>>,
+    /// Decompressed text shown in a separate window
+    earlier: Option<String>,
+}
+
+impl CompressedOutput {
+    fn new(max_lines: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<String>();
+        let compressed = Arc::new(Mutex::new(vec![]));
+
+        let buffer = Arc::clone(&compressed);
+        thread::spawn(move || {
+            for text in receiver {
+                let mut encoder = GzEncoder::new(vec![], Compression::default());
+                if encoder.write_all(text.as_bytes()).is_ok() {
+                    if let Ok(bytes) = encoder.finish() {
+                        buffer.lock().unwrap().extend(bytes);
+                    }
+                }
+            }
+        });
+
+        Self {
+            max_lines,
+            lines: 0,
+            sender,
+            compressed,
+            earlier: None,
+        }
+    }
+
+    fn compress_old(&mut self, output: &mut Vec<(u64, OutputType)>) {
+        let line_count = |text: &str| text.matches('\n').count();
+
+        let mut lines: usize = output
+            .iter()
+            .map(|(_, o)| match o {
+                OutputType::Text(text) => line_count(text),
+                OutputType::ProgressBar(..) => 0,
+            })
+            .sum();
+
+        if lines <= self.max_lines {
+            return;
+        }
+
+        // Down to half at once, so every gzip member holds a good amount of text.
+        // Progress bars are kept, they can still be updated
+        let keep = self.max_lines / 2;
+        let mut old = String::new();
+        output.retain(|(_, o)| match o {
+            OutputType::Text(text) if lines > keep => {
+                lines -= line_count(text);
+                self.lines += line_count(text);
+                old.push_str(text);
+                false
+            }
+            _ => true,
+        });
+
+        // Send returns error only if the thread panicked
+        drop(self.sender.send(old));
+    }
+
+    fn decompress(&self) -> String {
+        let compressed = self.compressed.lock().unwrap();
+        let mut text = String::new();
+        drop(MultiGzDecoder::new(&compressed[..]).read_to_string(&mut text));
+        text
     }
 }
 
@@ -72,72 +782,293 @@ impl Widget for &mut Output {
     fn ui(self, ui: &mut Ui) -> eframe::egui::Response {
         match self {
             Output::None => ui.vertical(|_| {}).response,
-            Output::Err(err) => ui.colored_label(Color32::RED, err.to_string()),
-            Output::Child(child, output) => {
-                // Update
-                let str = child.read();
-                let mut iter = str.split(MAGIC);
-
-                if let Some(text) = iter.next() {
-                    if !text.is_empty() {
-                        output.push((0, OutputType::Text(text.to_string())));
-                    }
+            Output::Err(ExecutionError::MatchError(err), settings) => {
+                clap_error_ui(ui, err, settings)
+            }
+            Output::Err(err, settings) => ui.colored_label(settings.error_color, err.to_string()),
+            Output::Child(child, output, state) => {
+                let running = child.is_running();
+                let response = output_ui(ui, output, state, running);
+                match child.error() {
+                    Some(err) => response | ui.colored_label(state.settings.error_color, err),
+                    None => response,
                 }
+            }
+            Output::Done(output, state, _) => output_ui(ui, output, state, false),
+        }
+    }
+}
 
-                while let Some(id) = iter.next() {
-                    if let Ok(id) = id.parse() {
-                        if let Some(new) = OutputType::parse(&mut iter) {
-                            if let Some((_, exists)) = output.iter_mut().find(|(i, _)| *i == id) {
-                                *exists = new;
-                            } else {
-                                output.push((id, new));
-                            }
+/// Output of a process run outside of klask, like with [`std::process::Command`] or
+/// `tokio::process::Command`, shown like the output of a finished run with its exit code.
+/// ```no_run
+/// # use eframe::egui;
+/// # use klask::output::ProcessOutput;
+/// # fn show(ui: &mut egui::Ui) {
+/// let process = std::process::Command::new("ls").output().unwrap();
+/// let mut output = ProcessOutput::new(process);
+/// ui.add(&mut output);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ProcessOutput(Output);
+
+impl ProcessOutput {
+    /// Stdout comes before stderr, invalid UTF-8 is replaced.
+    pub fn new(process: std::process::Output) -> Self {
+        Self(Output::from_completed_process(process))
+    }
+
+    /// None when the process was killed by a signal
+    pub fn exit_code(&self) -> Option<i32> {
+        match &self.0 {
+            Output::Done(.., code) => *code,
+            _ => None,
+        }
+    }
+
+    /// Printed text without ANSI codes and progress bars
+    pub fn text(&self) -> String {
+        self.0.text().unwrap_or_default()
+    }
+}
+
+impl Widget for &mut ProcessOutput {
+    fn ui(self, ui: &mut Ui) -> eframe::egui::Response {
+        let code = self.exit_code();
+        ui.vertical(|ui| {
+            if let (Some(code), Output::Done(_, state, _)) = (code, &self.0) {
+                ui.label(format!("{}: {code}", state.settings.localization.exit_code));
+            }
+            ui.add(&mut self.0);
+        })
+        .response
+    }
+}
+
+/// Shows the rendered clap error like program output, so ANSI styles are kept when
+/// clap is built with colors. Values that failed validation are also listed with
+/// their context.
+fn clap_error_ui(ui: &mut Ui, err: &clap::Error, settings: &OutputSettings) -> egui::Response {
+    ui.scope(|ui| {
+        // Parts without a style of their own
+        ui.visuals_mut().override_text_color = Some(settings.error_color);
+        format_output(ui, &err.render().to_string(), None, true);
+
+        if err.kind() == clap::error::ErrorKind::ValueValidation {
+            egui::Grid::new(("validation_context", settings.slot))
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (kind, value) in err.context() {
+                        if let Some(kind) = kind.as_str() {
+                            ui.strong(kind);
+                            ui.label(value.to_string());
+                            ui.end_row();
                         }
                     }
+                });
+        }
+    })
+    .response
+}
+
+fn output_ui(
+    ui: &mut Ui,
+    output: &mut [(u64, OutputType)],
+    state: &mut OutputState,
+    running: bool,
+) -> egui::Response {
+    ui.vertical(|ui| {
+        let localization = &*state.settings.localization;
+        if let Some(expected) = state.settings.expected_lines {
+            let lines_seen = state.lines_seen;
+            if running {
+                let progress = lines_seen as f32 / expected.max(1) as f32;
+                ui.add(ProgressBar::new(progress.min(1.0)).show_percentage());
+            } else {
+                ui.label(
+                    localization
+                        .output_done
+                        .replace("{lines}", &lines_seen.to_string()),
+                );
+            }
+
+            if lines_seen > expected {
+                ui.colored_label(Color32::YELLOW, &localization.more_output_than_expected);
+            }
+        }
 
-                    if let Some(text) = iter.next() {
-                        // Get rid of the newline
-                        let text = &text[1..];
-                        if !text.is_empty() {
-                            output.push((0, OutputType::Text(text.to_string())));
+        if let Some(compressed) = state.compressed.as_mut().filter(|c| c.lines > 0) {
+            if ui.button(&localization.load_earlier_output).clicked() {
+                compressed.earlier = Some(compressed.decompress());
+            }
+
+            if let Some(earlier) = &compressed.earlier {
+                let mut open = true;
+                egui::Window::new(&localization.earlier_output)
+                    .open(&mut open)
+                    .show(ui.ctx(), |ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            ui.add(TextEdit::multiline(&mut earlier.as_str()));
+                        });
+                    });
+
+                if !open {
+                    compressed.earlier = None;
+                }
+            }
+        }
+
+        // Shared by all outputs and kept between runs
+        let view_id = egui::Id::new("klask_output_view");
+        let mut view: OutputView = ui.data_mut(|d| d.get_temp(view_id).unwrap_or_default());
+        ui.horizontal(|ui| {
+            if ui.button("Copy output").clicked() {
+                let text = output
+                    .iter()
+                    .map(|(_, o)| match o {
+                        OutputType::Text(text) => text.as_str(),
+                        OutputType::ProgressBar(text, _) => text.as_str(),
+                    })
+                    .collect::<String>();
+                ui.ctx().output_mut(|o| {
+                    o.copied_text = match state.settings.strip_ansi {
+                        true => strip_ansi(&text),
+                        false => text,
+                    };
+                })
+            }
+
+            ui.checkbox(&mut view.line_numbers, &localization.line_numbers);
+            ui.checkbox(&mut view.wrap, &localization.wrap_lines);
+        });
+        ui.data_mut(|d| d.insert_temp(view_id, view));
+
+        let top = ui.cursor().min;
+        let keywords = state.settings.keyword_colors.as_deref();
+        if view == OutputView::default() {
+            for (_, o) in output.iter() {
+                match o {
+                    OutputType::Text(ref text) => format_output(ui, text, keywords, true),
+                    OutputType::ProgressBar(ref mess, value) => progress_bar_ui(ui, mess, *value),
+                }
+            }
+        } else {
+            let first = state.compressed.as_ref().map_or(0, |c| c.lines) + 1;
+            let rows = output_rows(output, first);
+            let last = rows.iter().rev().find_map(|row| match row {
+                OutputRow::Line(number, _) => Some(*number),
+                OutputRow::ProgressBar(..) => None,
+            });
+            let width = last.unwrap_or(first).to_string().len();
+
+            let show_rows = |ui: &mut Ui| {
+                for row in &rows {
+                    match row {
+                        OutputRow::Line(number, line) => {
+                            ui.horizontal(|ui| {
+                                if view.line_numbers {
+                                    let number = format!("{number:>width$} ");
+                                    ui.label(RichText::new(number).monospace().weak());
+                                }
+                                format_output(ui, line, keywords, view.wrap);
+                            });
                         }
+                        OutputRow::ProgressBar(mess, value) => progress_bar_ui(ui, mess, *value),
                     }
                 }
+            };
+            match view.wrap {
+                true => show_rows(ui),
+                false => {
+                    egui::ScrollArea::horizontal()
+                        .id_source("klask_output_rows")
+                        .show(ui, show_rows);
+                }
+            }
+        }
 
-                // View
-                ui.vertical(|ui| {
-                    if ui.button("Copy output").clicked() {
-                        ui.ctx().output_mut(|o| {
-                            o.copied_text = output
-                                .iter()
-                                .map(|(_, o)| match o {
-                                    OutputType::Text(text) => text,
-                                    OutputType::ProgressBar(text, _) => text,
-                                })
-                                .flat_map(|text| cansi::v3::categorise_text(text))
-                                .map(|slice| slice.text)
-                                .collect::<String>();
-                        })
-                    }
+        if state.new_output {
+            match state.settings.scroll_behavior {
+                ScrollBehavior::AlwaysBottom => ui.scroll_to_cursor(Some(Align::BOTTOM)),
+                ScrollBehavior::AlwaysTop => {
+                    ui.scroll_to_rect(Rect::from_min_size(top, Vec2::ZERO), Some(Align::TOP))
+                }
+                ScrollBehavior::UserControlled => {}
+            }
+        }
+    })
+    .response
+}
 
-                    for (_, o) in output {
-                        match o {
-                            OutputType::Text(ref text) => format_output(ui, text),
-                            OutputType::ProgressBar(ref mess, value) => {
-                                // Get rid of the ending newline
-                                ui.add(
-                                    ProgressBar::new(*value)
-                                        .text(&mess[..mess.len() - 1])
-                                        .animate(true),
-                                );
-                            }
+/// Display options picked in the output, they don't change copied or saved text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OutputView {
+    line_numbers: bool,
+    /// Wrap long lines instead of scrolling horizontally
+    wrap: bool,
+}
+
+impl Default for OutputView {
+    fn default() -> Self {
+        Self {
+            line_numbers: false,
+            wrap: true,
+        }
+    }
+}
+
+/// Output for showing line by line, see [`output_rows`]
+#[derive(Debug, PartialEq)]
+enum OutputRow<'a> {
+    /// Line number and text without the newline
+    Line(usize, String),
+    ProgressBar(&'a str, f32),
+}
+
+/// Joins the text of `output` and splits it into lines numbered from `first`.
+/// A line that a progress bar interrupts continues with the same number after it.
+fn output_rows(output: &[(u64, OutputType)], first: usize) -> Vec<OutputRow<'_>> {
+    let mut rows = vec![];
+    let mut line = String::new();
+    let mut number = first;
+
+    for (_, o) in output {
+        match o {
+            OutputType::Text(text) => {
+                for part in text.split_inclusive('\n') {
+                    match part.strip_suffix('\n') {
+                        Some(part) => {
+                            line.push_str(part);
+                            rows.push(OutputRow::Line(number, std::mem::take(&mut line)));
+                            number += 1;
                         }
+                        None => line.push_str(part),
                     }
-                })
-                .response
+                }
+            }
+            OutputType::ProgressBar(mess, value) => {
+                if !line.is_empty() {
+                    rows.push(OutputRow::Line(number, std::mem::take(&mut line)));
+                }
+                rows.push(OutputRow::ProgressBar(mess, *value));
             }
         }
     }
+
+    if !line.is_empty() {
+        rows.push(OutputRow::Line(number, line));
+    }
+    rows
+}
+
+fn progress_bar_ui(ui: &mut Ui, mess: &str, value: f32) {
+    // Get rid of the ending newline
+    ui.add(
+        ProgressBar::new(value)
+            .text(&mess[..mess.len() - 1])
+            .animate(true),
+    );
 }
 
 #[derive(Debug)]
@@ -186,13 +1117,17 @@ impl OutputType {
     }
 }
 
-fn format_output(ui: &mut Ui, text: &str) {
+/// Shows text with its ANSI styles and clickable links. Parts matching `keywords`
+/// are colored unless they have an ANSI color. Without `wrap` the text is one row
+/// as wide as it needs, so it should be a single line.
+fn format_output(ui: &mut Ui, text: &str, keywords: Option<&KeywordColors>, wrap: bool) {
     let output = cansi::v3::categorise_text(text);
 
     let previous = ui.style().spacing.item_spacing;
     ui.style_mut().spacing.item_spacing = vec2(0.0, 0.0);
 
-    ui.horizontal_wrapped(|ui| {
+    let add_slices = |ui: &mut Ui| {
+        ui.style_mut().wrap = Some(wrap);
         for CategorisedSlice {
             text,
             fg,
@@ -206,47 +1141,59 @@ fn format_output(ui: &mut Ui, text: &str) {
         {
             for span in LinkFinder::new().spans(text) {
                 match span.kind() {
-                    Some(LinkKind::Url) => ui.hyperlink(span.as_str()),
+                    Some(LinkKind::Url) => {
+                        ui.hyperlink(span.as_str());
+                    }
                     Some(LinkKind::Email) => {
-                        ui.hyperlink_to(span.as_str(), format!("mailto:{}", span.as_str()))
+                        ui.hyperlink_to(span.as_str(), format!("mailto:{}", span.as_str()));
                     }
                     Some(_) | None => {
-                        let mut text = RichText::new(span.as_str());
+                        let parts = match keywords {
+                            Some(keywords) if fg.is_none() => keywords.split(span.as_str()),
+                            _ => vec![(span.as_str(), None)],
+                        };
+                        for (part, keyword_color) in parts {
+                            let mut text = RichText::new(part);
 
-                        if let Some(fg) = fg {
-                            text = text.color(ansi_color_to_egui(fg));
-                        }
+                            if let Some(color) = fg.map(ansi_color_to_egui).or(keyword_color) {
+                                text = text.color(color);
+                            }
 
-                        if let Some(bg) = bg {
-                            if bg != Color::Black {
-                                text = text.background_color(ansi_color_to_egui(bg));
+                            if let Some(bg) = bg {
+                                if bg != Color::Black {
+                                    text = text.background_color(ansi_color_to_egui(bg));
+                                }
                             }
-                        }
 
-                        if italic == Some(true) {
-                            text = text.italics();
-                        }
+                            if italic == Some(true) {
+                                text = text.italics();
+                            }
 
-                        if underline == Some(true) {
-                            text = text.underline();
-                        }
+                            if underline == Some(true) {
+                                text = text.underline();
+                            }
 
-                        if strikethrough == Some(true) {
-                            text = text.strikethrough();
-                        }
+                            if strikethrough == Some(true) {
+                                text = text.strikethrough();
+                            }
 
-                        text = match intensity {
-                            Some(Intensity::Bold) => text.strong(),
-                            Some(Intensity::Faint) => text.weak(),
-                            Some(Intensity::Normal) | None => text,
-                        };
+                            text = match intensity {
+                                Some(Intensity::Bold) => text.strong(),
+                                Some(Intensity::Faint) => text.weak(),
+                                Some(Intensity::Normal) | None => text,
+                            };
 
-                        ui.add(Label::new(text))
+                            ui.add(Label::new(text));
+                        }
                     }
-                };
+                }
             }
         }
-    });
+    };
+    match wrap {
+        true => ui.horizontal_wrapped(add_slices),
+        false => ui.horizontal(add_slices),
+    };
     ui.style_mut().spacing.item_spacing = previous;
 }
 
@@ -270,3 +1217,6 @@ fn ansi_color_to_egui(color: Color) -> Color32 {
         Color::BrightWhite => Color32::from_rgb(229, 229, 229),
     }
 }
+
+#[cfg(test)]
+mod tests;
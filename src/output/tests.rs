@@ -0,0 +1,240 @@
+use super::{
+    decode_output, format_elapsed, format_timestamp, output_rows, strip_ansi, take_field_errors,
+    CompressedOutput, KeywordColors, Output, OutputRow, OutputType, ProcessOutput,
+};
+use eframe::egui::Color32;
+use std::collections::HashMap;
+use std::process::ExitStatus;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[test]
+fn strip_ansi_codes() {
+    assert_eq!(strip_ansi("plain text\n"), "plain text\n");
+    assert_eq!(
+        strip_ansi("\x1b[1;31merror\x1b[0m: \x1b[4mfile\x1b[24m"),
+        "error: file"
+    );
+    // Cursor movement and other CSI sequences
+    assert_eq!(strip_ansi("a\x1b[2Kb\x1b[10;5Hc"), "abc");
+    // OSC hyperlinks, terminated by BEL or ST
+    assert_eq!(
+        strip_ansi("\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\"),
+        "link"
+    );
+    // Unicode is kept
+    assert_eq!(strip_ansi("\x1b[32m✔ ok ąę\x1b[m"), "✔ ok ąę");
+}
+
+#[test]
+fn timestamps() {
+    let at = |secs| format_timestamp(UNIX_EPOCH + Duration::from_secs(secs));
+    assert_eq!(at(0), "1970-01-01 00:00:00");
+    assert_eq!(at(1_705_328_585), "2024-01-15 14:23:05");
+    // Leap day
+    assert_eq!(at(951_782_400), "2000-02-29 00:00:00");
+}
+
+#[test]
+fn elapsed() {
+    let elapsed = |secs| format_elapsed(Duration::from_secs(secs));
+    assert_eq!(elapsed(12), "00:12");
+    assert_eq!(elapsed(125), "02:05");
+    assert_eq!(elapsed(3 * 3600 + 61), "3:01:01");
+}
+
+#[test]
+fn completed_process() {
+    let mut output = Output::from_completed_process(std::process::Output {
+        status: ExitStatus::default(),
+        stdout: b"out\n".to_vec(),
+        stderr: b"err \xff\n".to_vec(),
+    });
+    assert_eq!(output.exit_code(), Some(0));
+    assert!(!output.failed());
+    assert!(!output.is_running());
+    assert_eq!(output.elapsed(), None);
+
+    let Output::Done(lines, ..) = &output else {
+        panic!("expected a finished output");
+    };
+    let text: Vec<_> = lines
+        .iter()
+        .map(|(_, o)| match o {
+            OutputType::Text(text) => text.as_str(),
+            OutputType::ProgressBar(text, _) => text.as_str(),
+        })
+        .collect();
+    assert_eq!(text, ["out\n", "err \u{FFFD}\n"]);
+}
+
+#[test]
+fn finished_text() {
+    let output = Output::from_completed_process(std::process::Output {
+        status: ExitStatus::default(),
+        stdout: b"\x1b[32mgreen\x1b[0m\n".to_vec(),
+        stderr: b"warning\n".to_vec(),
+    });
+    assert_eq!(output.text().as_deref(), Some("green\nwarning\n"));
+    assert_eq!(Output::None.text(), None);
+}
+
+#[test]
+fn recently_saved() {
+    let mut output = Output::from_completed_process(std::process::Output {
+        status: ExitStatus::default(),
+        stdout: b"done\n".to_vec(),
+        stderr: vec![],
+    });
+    assert!(output.recently_saved().is_none());
+
+    output.set_saved("out.txt".into());
+    let (path, left) = output.recently_saved().unwrap();
+    assert_eq!(path, std::path::Path::new("out.txt"));
+    assert!(left <= Duration::from_secs(3));
+
+    let mut none = Output::None;
+    none.set_saved("out.txt".into());
+    assert!(none.recently_saved().is_none());
+}
+
+#[test]
+fn invalid_utf8() {
+    let bytes = b"text\n\xff\x00ok\n\xc3";
+    assert_eq!(decode_output(bytes, false), "text\n\u{fffd}\0ok\n\u{fffd}");
+    assert_eq!(decode_output(bytes, true), "text\nff 00 6f 6b\nc3");
+    assert_eq!(decode_output("ünïcode\n".as_bytes(), true), "ünïcode\n");
+}
+
+#[test]
+fn keyword_colors() {
+    let map = HashMap::from([
+        ("ERROR".to_string(), Color32::RED),
+        ("WARN".to_string(), Color32::YELLOW),
+        ("WARNING".to_string(), Color32::GOLD),
+    ]);
+
+    let colors = KeywordColors::new(map.clone(), false);
+    assert_eq!(
+        colors.split("error: a Warning"),
+        [
+            ("error", Some(Color32::RED)),
+            (": a ", None),
+            ("Warning", Some(Color32::GOLD)),
+        ]
+    );
+    assert_eq!(colors.split("fine"), [("fine", None)]);
+
+    let colors = KeywordColors::new(map, true);
+    assert_eq!(
+        colors.split("error ERROR"),
+        [("error ", None), ("ERROR", Some(Color32::RED))]
+    );
+}
+
+#[test]
+fn line_rows() {
+    let output = [
+        (0, OutputType::Text("first\nsecond ".into())),
+        (1, OutputType::ProgressBar("bar\n".into(), 0.5)),
+        (0, OutputType::Text("continued\nlast".into())),
+    ];
+    assert_eq!(
+        output_rows(&output, 5),
+        [
+            OutputRow::Line(5, "first".into()),
+            OutputRow::Line(6, "second ".into()),
+            OutputRow::ProgressBar("bar\n", 0.5),
+            OutputRow::Line(6, "continued".into()),
+            OutputRow::Line(7, "last".into()),
+        ]
+    );
+}
+
+#[test]
+fn field_errors() {
+    let mut errors = vec![];
+    let text =
+        "before\nKLASK_FIELD_ERROR:output:Exists: a.txt\r\nafter\nKLASK_FIELD_ERROR:no message\n";
+    assert_eq!(
+        take_field_errors(text, &mut errors),
+        "before\nafter\nKLASK_FIELD_ERROR:no message\n"
+    );
+    assert_eq!(errors, [("output".into(), "Exists: a.txt".into())]);
+}
+
+#[test]
+fn progress_lines() {
+    let output = Output::from_completed_process(std::process::Output {
+        status: ExitStatus::default(),
+        stdout: b"start\nKLASK_PROGRESS:0.5:Half\nKLASK_PROGRESS:1.5\nend\n".to_vec(),
+        stderr: vec![],
+    });
+    let Output::Done(lines, ..) = &output else {
+        panic!("expected a finished output");
+    };
+    let lines: Vec<_> = lines
+        .iter()
+        .map(|(_, o)| match o {
+            OutputType::Text(text) => (text.as_str(), None),
+            OutputType::ProgressBar(text, value) => (text.as_str(), Some(*value)),
+        })
+        .collect();
+    // The second line updates the bar in place and is clamped
+    assert_eq!(
+        lines,
+        [("start\n", None), ("\n", Some(1.0)), ("end\n", None)]
+    );
+    assert_eq!(output.text().as_deref(), Some("start\nend\n"));
+}
+
+#[test]
+fn compress_in_batches() {
+    let mut compressed = CompressedOutput::new(10);
+    let mut output: Vec<_> = (0..10)
+        .map(|i| (i, OutputType::Text(format!("line {i}\n"))))
+        .collect();
+    compressed.compress_old(&mut output);
+    assert_eq!((output.len(), compressed.lines), (10, 0));
+
+    // Going over the limit compresses down to half of it
+    output.push((10, OutputType::Text("line 10\n".into())));
+    compressed.compress_old(&mut output);
+    assert_eq!((output.len(), compressed.lines), (5, 6));
+    assert!(matches!(&output[0].1, OutputType::Text(text) if text == "line 6\n"));
+
+    output.push((11, OutputType::Text("line 11\n".into())));
+    compressed.compress_old(&mut output);
+    assert_eq!((output.len(), compressed.lines), (6, 6));
+}
+
+#[test]
+fn saved_text_with_note() {
+    let mut output = Output::from_completed_process(std::process::Output {
+        status: ExitStatus::default(),
+        stdout: b"result\n".to_vec(),
+        stderr: vec![],
+    });
+    assert_eq!(output.saved_text().as_deref(), Some("result\n"));
+
+    output.set_note(Some(String::new()));
+    assert_eq!(output.note(), None);
+
+    output.set_note(Some("second try\nwith -v".into()));
+    assert_eq!(
+        output.saved_text().as_deref(),
+        Some("# Note: second try with -v\nresult\n")
+    );
+    // Only saving starts with the note, not stdin from the output
+    assert_eq!(output.text().as_deref(), Some("result\n"));
+}
+
+#[test]
+fn process_output() {
+    let output = ProcessOutput::new(std::process::Output {
+        status: ExitStatus::default(),
+        stdout: b"\x1b[32mout\x1b[0m\n".to_vec(),
+        stderr: b"err\n".to_vec(),
+    });
+    assert_eq!(output.exit_code(), Some(0));
+    assert_eq!(output.text(), "out\nerr\n");
+}
@@ -0,0 +1,60 @@
+use crate::{child_app::InProcess, Klask, Settings};
+use clap::{ArgMatches, Command};
+use eframe::egui::{Response, Ui, Widget};
+use std::fmt::Display;
+
+/// The form, run button and output of klask as a widget, for adding to an existing
+/// egui app instead of opening a window with [`crate::run_app`]. The closure is called
+/// on a thread like with [`crate::run_app_in_process`], so the same limitations apply.
+///
+/// Keep the panel in the state of your app, it holds the entered values and the
+/// running programs.
+/// ```no_run
+/// # use clap::{arg, Command};
+/// # use eframe::egui;
+/// # use klask::{KlaskPanel, Settings};
+/// struct MyApp {
+///     panel: KlaskPanel,
+/// }
+///
+/// impl eframe::App for MyApp {
+///     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+///         egui::SidePanel::left("klask").show(ctx, |ui| {
+///             ui.add(&mut self.panel);
+///         });
+///     }
+/// }
+///
+/// let app = Command::new("Example").arg(arg!(--name <NAME>));
+/// let panel = KlaskPanel::new(app, Settings::default(), |matches| {
+///     println!("Hello {}", matches.get_one::<String>("name").unwrap());
+///     Ok::<_, String>(())
+/// });
+/// ```
+#[derive(Debug)]
+pub struct KlaskPanel(Klask);
+
+impl KlaskPanel {
+    /// Fonts and [`Settings::style`] are left to the app. [`Settings::run_sequence`],
+    /// [`Settings::batch_arg`] and [`Settings::confirm_close_while_running`] aren't
+    /// supported.
+    ///
+    /// # Panics
+    ///
+    /// If [`Settings::localization`] has empty fields, see [`crate::Localization::validate`].
+    pub fn new<E: Display>(
+        app: Command,
+        settings: Settings,
+        f: impl Fn(&ArgMatches) -> Result<(), E> + Send + Sync + 'static,
+    ) -> Self {
+        let klask = Klask::new(app, settings, Some(InProcess::new(f)));
+        klask.assert_localization();
+        Self(klask)
+    }
+}
+
+impl Widget for &mut KlaskPanel {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| self.0.ui(ui)).response
+    }
+}
@@ -0,0 +1,53 @@
+use clap::{ArgAction, ArgMatches, Command};
+use serde_json::{Map, Value};
+use std::ffi::OsString;
+
+/// Hidden flag that makes a relaunched binary print its args instead of running
+pub const FLAG: &str = "--klask-print-args";
+
+/// Removes every [`FLAG`] from `args`, returns whether there was one.
+pub fn take_flag(args: &mut Vec<OsString>) -> bool {
+    let len = args.len();
+    args.retain(|arg| arg != FLAG);
+    args.len() != len
+}
+
+/// Converts matches into a JSON object keyed by arg id. Flags become booleans,
+/// counted flags numbers, args with multiple values arrays and the rest strings.
+/// Args without a value are left out. The used subcommand is an object under its name.
+pub fn to_value(app: &Command, matches: &ArgMatches) -> Value {
+    let mut object = Map::new();
+
+    for arg in app.get_arguments() {
+        let id = arg.get_id().as_str();
+        let value = match arg.get_action() {
+            ArgAction::SetTrue | ArgAction::SetFalse => Value::Bool(matches.get_flag(id)),
+            ArgAction::Count => matches.get_count(id).into(),
+            ArgAction::Set | ArgAction::Append => {
+                let Some(values) = matches.get_raw(id) else {
+                    continue;
+                };
+                let mut values = values.map(|value| value.to_string_lossy().into_owned());
+                let multiple = matches!(arg.get_action(), ArgAction::Append)
+                    || arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+                match multiple {
+                    true => values.collect::<Vec<_>>().into(),
+                    false => match values.next() {
+                        Some(value) => value.into(),
+                        None => continue,
+                    },
+                }
+            }
+            _ => continue,
+        };
+        object.insert(id.to_string(), value);
+    }
+
+    if let Some((name, sub_matches)) = matches.subcommand() {
+        if let Some(subcommand) = app.find_subcommand(name) {
+            object.insert(name.to_string(), to_value(subcommand, sub_matches));
+        }
+    }
+
+    Value::Object(object)
+}
@@ -1,11 +1,29 @@
 // Structs are marked as `#[non_exhaustive]` to allow
 // to add other optionas alter withour breaking compatibility.
 
-use eframe::egui::{self, style::Spacing, Style};
-use std::borrow::Cow;
+use clap::Command;
+use eframe::egui::{self, style::Spacing, Color32, Stroke, Style, Visuals};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+mod locales;
 
 /// Settings for klask.
-/// Is marked with `#[non_exhaustive]` so you must construct it like this
+/// Is marked with `#[non_exhaustive]` so you must construct it with [`Settings::builder`]
+/// ```
+/// # use klask::{Settings, Theme};
+/// let settings = Settings::builder()
+///     .env("Description")
+///     .theme(Theme::Dark)
+///     .build();
+/// ```
+/// or by changing the defaults
 /// ```
 /// # use klask::Settings;
 /// let mut settings = Settings::default();
@@ -17,34 +35,489 @@ pub struct Settings {
     /// Pass None to disable. Pass Some with a description to enable.
     /// Pass an empty String for no description.
     pub enable_env: Option<String>,
+    /// Variables the environment variables tab starts with, like `RUST_LOG=info`.
+    /// Only used together with `enable_env`. Default is empty.
+    pub env_default: Vec<(String, String)>,
+    /// Environment variables the program reads. Together with variables of args set
+    /// with [`clap::Arg::env`] they are marked in the environment variables tab,
+    /// so typos stand out.
+    pub known_env_vars: Vec<String>,
+    /// Called in order under the environment variables, for custom widgets like a button
+    /// that loads variables from somewhere. They can add, change and remove variables.
+    /// ```
+    /// # use klask::{EnvExtraUi, Settings};
+    /// let level = String::from("info");
+    /// let mut settings = Settings::default();
+    /// settings.enable_env = Some(String::new());
+    /// settings.env_extra_ui.push(EnvExtraUi::new(move |ui, env| {
+    ///     if ui.button("Add defaults").clicked() {
+    ///         env.push(("RUST_LOG".into(), level.clone()));
+    ///     }
+    /// }));
+    /// ```
+    pub env_extra_ui: Vec<EnvExtraUi>,
     /// Pass None to disable. Pass Some with a description to enable.
     /// Pass an empty String for no description.
     pub enable_stdin: Option<String>,
+    /// Text the stdin editor starts with. Only used together with `enable_stdin`.
+    /// Default is None.
+    pub stdin_default: Option<String>,
+    /// Called in order under the stdin editor with its text, or the path when a file is picked.
+    pub stdin_extra_ui: Vec<StdinExtraUi>,
+    /// Hint shown inside the empty stdin text editor, and also as a grey label under
+    /// it so it stays visible while typing. Falls back to
+    /// [`Localization::stdin_placeholder`] (only as a hint) when None.
+    pub stdin_description_placeholder: Option<String>,
+    /// Largest stdin text or file in bytes. Above it a warning is shown and
+    /// running is disabled. Only checked in the GUI. Default is None.
+    pub stdin_max_bytes: Option<usize>,
     /// Pass None to disable. Pass Some with a description to enable.
     /// Pass an empty String for no description.
     pub enable_working_dir: Option<String>,
+    /// Initial value of the working directory field from `enable_working_dir`. The field
+    /// shows a warning if the directory doesn't exist when the GUI starts. Default is None.
+    pub working_dir_default: Option<PathBuf>,
+    /// Don't allow changing the working directory field, so programs always run in
+    /// `working_dir_default`. Default is `false`.
+    pub working_dir_read_only: bool,
+    /// Tab shown when the window opens. A tab that isn't enabled falls back to the
+    /// arguments tab. Default is [`DefaultTab::Arguments`].
+    pub default_tab: DefaultTab,
+    /// Tabs shown after the arguments, environment variables and input tabs.
+    /// They don't change what is passed to the program.
+    /// ```
+    /// # use klask::{ExtraTab, Settings, TabContent};
+    /// struct Help;
+    ///
+    /// impl TabContent for Help {
+    ///     fn label(&self) -> &str {
+    ///         "Help"
+    ///     }
+    ///
+    ///     fn ui(&mut self, ui: &mut eframe::egui::Ui) {
+    ///         ui.label("Pick a file and press run");
+    ///     }
+    /// }
+    ///
+    /// let mut settings = Settings::default();
+    /// settings.extra_tabs.push(ExtraTab::new(Help));
+    /// ```
+    pub extra_tabs: Vec<ExtraTab>,
     /// Pass a custom font to be used in the GUI.
     /// ```ignore
     /// let mut settings = Settings::default();
     /// settings.custom_font = Some(Cow::Borrowed(include_bytes!(r"FONT_PATH")));
     /// ```
     pub custom_font: Option<Cow<'static, [u8]>>,
+    /// Hide all buttons that open native file and directory dialogs, so paths have to
+    /// be typed. For environments where the dialogs don't work, like some headless
+    /// CI runners or Wayland compositors without an XDG portal. Default is `false`.
+    pub disable_file_dialogs: bool,
+    /// Show a button next to text fields with a searchable list of the environment
+    /// variables of the GUI. Picking one fills in its value. Default is `false`.
+    pub enable_env_paste: bool,
+    /// Only list environment variables starting with this prefix in the list of
+    /// `enable_env_paste`. Default is None.
+    pub env_paste_filter: Option<String>,
+    /// Pass a path to a toml file to record how often each argument is changed
+    /// from its default. Counts are only updated when a run finishes successfully.
+    /// After a few runs a button for viewing the stats is shown.
+    pub usage_stats_path: Option<PathBuf>,
+    /// Text fields remember the last few values they were run with, picked from a
+    /// button next to them. Pass a path to a file to keep them between sessions.
+    /// Default is `None`, which only keeps them until the window is closed.
+    pub field_history_path: Option<PathBuf>,
+    /// Show validation errors as a label under the argument. When `false` they
+    /// are only shown when hovering over the argument. Default is `true`.
+    pub inline_validation_errors: bool,
+    /// Pass Some to fill the run button with this color while the form is valid, so it's
+    /// clear whether running will succeed. The check runs again only when the command
+    /// line changes. Default is `None`.
+    pub run_button_ready_color: Option<Color32>,
+    /// Show the long help of arguments as text below their value instead of only when
+    /// hovering over the name, where long texts are cramped. The short help is still
+    /// shown when hovering. Arguments and commands with `next_line_help` always show it
+    /// below. Default is `false`.
+    pub inline_long_help: bool,
+    /// Order arguments are shown in, separately for every subcommand.
+    /// Default is [`ArgSort::Definition`].
+    pub arg_sort: ArgSort,
+    /// Ids of arguments shown first, top to bottom, without changing the order on the
+    /// command line. Arguments that aren't listed follow in the order of `arg_sort`.
+    /// Subcommands use the same list.
+    pub arg_order: Vec<String>,
+    /// Arguments that are only shown while another argument has some value, as
+    /// `(trigger_id, trigger_value, ids)`. The arguments in `ids` are hidden unless the
+    /// argument `trigger_id` of the same command has the value `trigger_value`, and an
+    /// argument listed in several entries is shown when any of them matches. Flags have
+    /// the value `true` or `false` and counted flags their count. Hidden arguments are
+    /// cleared and left out of the command line.
+    /// ```
+    /// # use klask::Settings;
+    /// let mut settings = Settings::default();
+    /// settings.conditional_visibility.push((
+    ///     "format".into(),
+    ///     "json".into(),
+    ///     vec!["indent-depth".into()],
+    /// ));
+    /// ```
+    pub conditional_visibility: Vec<(String, String, Vec<String>)>,
+    /// Color of outlines and messages for invalid values. Default is red.
+    pub error_color: Color32,
+    /// Color of hints for values that might be wrong, like malformed email addresses
+    /// and unresolved templates. Default is yellow.
+    pub warning_color: Color32,
+    /// Extra checks for argument values, keyed by arg id. They are called with every
+    /// entered value after clap's own validation passes, and an `Err` message is shown
+    /// on the argument like other validation errors.
+    /// ```
+    /// # use klask::{Settings, Validator};
+    /// let mut settings = Settings::default();
+    /// settings.validators.insert(
+    ///     "output".into(),
+    ///     Validator::new(|value| match std::path::Path::new(value).exists() {
+    ///         true => Err("File already exists".into()),
+    ///         false => Ok(()),
+    ///     }),
+    /// );
+    /// ```
+    pub validators: HashMap<String, Validator>,
+    /// Show a notes field above the run button, for remembering what each run was for.
+    /// Notes are not passed to the program. Earlier notes can be picked from a popup and
+    /// are saved with `field_history_path`. Saved output and the `log_file` start with a
+    /// `# Note: <notes>` line. Default is `false`.
+    pub enable_run_notes: bool,
+    /// Keep the notes after a run instead of clearing them. Default is `false`.
+    pub persist_notes: bool,
+    /// Compress older output so long running programs don't use up all the memory.
+    /// It can still be viewed with the [`Localization::load_earlier_output`] button.
+    /// Default is `false`.
+    pub compress_old_output: bool,
+    /// Clear the output of the previous run when running again. When `false`,
+    /// runs are kept and divided by `output_line_separator`. Default is `true`.
+    pub clear_output_on_run: bool,
+    /// Text between the output of consecutive runs when `clear_output_on_run`
+    /// is `false`. Default is `"\n---\n"`.
+    pub output_line_separator: String,
+    /// Replace `output_line_separator` with the time the run started, in UTC, like
+    /// `"\n--- 2024-01-15 14:23:05 ---\n"`. Default is `false`.
+    pub run_separator_timestamp: bool,
+    /// Don't show the output of a run, only the running indicator. Once the run
+    /// exits, a summary with its exit code and a button for showing the output
+    /// appear. Default is `false`.
+    pub hide_output_on_run: bool,
+    /// How many lines of output are kept uncompressed when `compress_old_output`
+    /// is enabled. Past that, older lines are compressed until half of them are left.
+    /// Default is 10000.
+    pub output_max_lines_before_compress: usize,
+    /// Pass Some with the number of lines the program is expected to print to show
+    /// a progress bar based on the lines printed so far.
+    pub expected_output_lines: Option<usize>,
+    /// Remove ANSI escape codes (like colors) from copied and saved output.
+    /// Pass `false` to keep them, for example for pasting into a terminal. Default is `true`.
+    pub output_strip_ansi: bool,
+    /// Output that isn't valid UTF-8 is shown with `�` in place of the invalid bytes.
+    /// Pass `true` to show such lines as hex bytes instead, for programs that print
+    /// binary data. Default is `false`.
+    pub output_binary_as_hex: bool,
+    /// Colors for words in the output, like `ERROR` in red. The keys are matched
+    /// literally, or as regular expressions with the `regex` feature (keys that
+    /// aren't valid expressions are matched literally). Text colored with ANSI
+    /// codes keeps its color.
+    /// ```
+    /// # use klask::Settings;
+    /// # use eframe::egui::Color32;
+    /// let mut settings = Settings::default();
+    /// settings.output_color_map.insert("ERROR".into(), Color32::RED);
+    /// settings.output_color_map.insert("WARN".into(), Color32::YELLOW);
+    /// ```
+    pub output_color_map: HashMap<String, Color32>,
+    /// Match the keys of `output_color_map` with the same case only. Default is `false`.
+    pub output_highlight_case_sensitive: bool,
+    /// Show a button that copies the arguments and selected subcommands as a JSON object,
+    /// like for pasting them into a bug report. Default is `false`.
+    pub enable_copy_json: bool,
+    /// Show a button for moving the output into its own window, like to a second monitor.
+    /// Default is `false`.
+    pub enable_output_popout: bool,
+    /// Show a button for scrolling to the first required argument without a value, also
+    /// triggered with F8. The key is taken from the app the GUI is embedded in, see
+    /// [`crate::KlaskPanel`]. Default is `false`.
+    pub enable_go_to_missing: bool,
+    /// Show a collapsible summary of the arguments changed from their defaults under the
+    /// form. Default is `false`.
+    pub enable_changes_summary: bool,
+    /// Show a button for closing the window while the program keeps running in the
+    /// background. The GUI exits, the program goes on printing into `log_file`.
+    /// Not supported with [`crate::run_app_in_process`]. Default is `false`.
+    pub enable_detach: bool,
+    /// Start the program through `sh -c` (`cmd /C` on windows) with this appended to its
+    /// command line as it is, like `2>&1 | tee run.log`, for wrappers around commands that
+    /// need a shell. The suffix is interpreted by the shell, so it must come from the app
+    /// and not from users. The values of the form are still quoted and reach the program
+    /// as typed. The exact line of the main command is shown below the Run button.
+    /// Killing a run only stops the shell when it didn't replace itself with the program.
+    /// Not supported with [`crate::run_app_in_process`]. Default is `None`.
+    pub shell_suffix: Option<String>,
+    /// Let the program show errors on arguments of the form by printing lines like
+    /// `KLASK_FIELD_ERROR:<id>:<message>`, see [`crate::output::field_error`]. The lines
+    /// aren't shown in the output. Only the arguments of the main command (not the
+    /// steps of `run_sequence`) are looked up. Default is `false`.
+    pub enable_field_errors: bool,
+    /// File the output of programs is appended to when they can keep running after the
+    /// window is closed, with `enable_detach` or without `kill_on_close`. Such programs
+    /// write to a file the output is read back from instead of to pipes, so closing the
+    /// window or the terminal doesn't stop them.
+    /// Concurrent runs after the first get their own file, with the number of the run
+    /// added to the name like `klask-2.log`. Without one a temporary file is used and
+    /// the output after closing is discarded.
+    pub log_file: Option<PathBuf>,
+    /// Where the view scrolls when a running program prints something.
+    /// Default is [`ScrollBehavior::AlwaysBottom`].
+    pub output_scroll_behavior: ScrollBehavior,
+    /// Show a button for saving the output of a finished run to a file. Default is `false`.
+    pub enable_save_output: bool,
+    /// Extension the save dialog of `enable_save_output` filters for. Default is `"txt"`.
+    pub output_file_extension: String,
+    /// How many runs can be running at the same time. When more than 1, every run
+    /// gets its own output tab showing the elapsed time and exit code. Always 1 with
+    /// [`crate::run_app_in_process`], since its runs redirect the output of the whole
+    /// process. Default is 1.
+    pub max_concurrent_runs: usize,
+    /// Commands that are run one after another after the main one, each with its own
+    /// arguments. The sequence stops at the first step that fails. The closure is called
+    /// with the matches of every step, use [`crate::current_step`] to tell them apart.
+    /// Not supported with [`crate::run_app_in_process`] and [`crate::run_derived`].
+    /// ```
+    /// # use clap::Command;
+    /// # use klask::Settings;
+    /// let mut settings = Settings::default();
+    /// settings.run_sequence.push(|| Command::new("Upload"));
+    /// ```
+    pub run_sequence: Vec<fn() -> Command>,
+    /// Name of the environment variable that tells the relaunched binary to run the
+    /// closure instead of the GUI. Pass Some to avoid clashes, like when a klask app
+    /// starts another one. Default is `None`, which uses `KLASK_CHILD_APP`.
+    pub child_env_var: Option<String>,
+    /// Id of a multiple value argument to run the program once per value of. Every run
+    /// gets one of the values and the rest of the form as is. The runs go one after
+    /// another into the same output, each with a header and its exit code.
+    /// Not used together with `run_sequence` or [`crate::run_app_in_process`].
+    pub batch_arg: Option<String>,
+    /// Shows a button that fills every argument with a random value, for exploratory
+    /// testing. Requires the `randomize` feature. Default is `false`.
+    #[cfg(feature = "randomize")]
+    pub enable_randomize: bool,
+    /// Running the relaunched binary with `--klask-print-args` prints the args as a JSON
+    /// object and exits instead of calling the closure, so scripts can reuse the values
+    /// entered in the GUI. Requires the `print-args` feature. Default is `false`.
+    #[cfg(feature = "print-args")]
+    pub enable_print_args: bool,
+    /// Shows a menu for saving or copying a man page of the command, generated with
+    /// `clap_mangen`. Requires the `man` feature. Default is `false`.
+    #[cfg(feature = "man")]
+    pub enable_man_page: bool,
+    /// Ask whether to kill the running programs when the window is closed during a run.
+    /// Keeping them running is only offered with `enable_detach` or without
+    /// `kill_on_close`, they print into [`Settings::log_file`] then. Default is `false`.
+    pub confirm_close_while_running: bool,
+    /// Kill programs that are still running when the window is closed without the
+    /// confirmation, otherwise they're left running in the background. Default is `true`.
+    pub kill_on_close: bool,
+    /// Shows a panel on the right with the help of the field that was focused or
+    /// clicked last, instead of only when hovering over its name. Not shown by
+    /// [`crate::KlaskPanel`]. Default is `false`.
+    pub show_help_panel: bool,
+    /// How subcommands are picked. Default is [`SubcommandDisplay::Dropdown`].
+    pub subcommand_display: SubcommandDisplay,
+    /// Called when the window closes, after running programs were killed or left running.
+    /// Useful for cleanup like removing temporary files. Not called by
+    /// [`crate::KlaskPanel`], which doesn't own the window. Default is `None`.
+    /// ```
+    /// # use klask::{Settings, WindowCloseHook};
+    /// let mut settings = Settings::default();
+    /// settings.on_window_close = Some(WindowCloseHook::new(|| println!("Bye")));
+    /// ```
+    pub on_window_close: Option<WindowCloseHook>,
+    /// Keeps the window above other windows, like for a tool that stays visible on a second
+    /// monitor. Not supported on Wayland and ignored by [`crate::KlaskPanel`].
+    /// Default is `false`.
+    pub window_always_on_top: bool,
+    /// Pass counted flags in the combined form, like `-vvv` instead of `-v -v -v`.
+    /// Only applies to flags with a short name. Default is `false`.
+    pub combine_short_flags: bool,
+    /// Show the file and directory pickers for args without a [`clap::ValueHint`] whose
+    /// id ends with `_file`, `_dir` or `_path`. Default is `false`.
+    pub infer_path_from_name: bool,
+    /// How often the GUI is repainted while a run is in progress, for the running
+    /// animation and elapsed time. New output is always shown immediately and nothing
+    /// is repainted when idle. Pass None to only repaint on new output.
+    /// Default is 100 ms (10 Hz).
+    pub repaint_interval: Option<Duration>,
+    /// How a run in progress is shown next to the run button.
+    /// Default is [`RunningIndicator::Dots`].
+    pub running_indicator: RunningIndicator,
+    /// Show [`Localization::ready`] before the first run and [`Localization::finished`]
+    /// once the shown run is done. Default is `false`.
+    pub show_run_status: bool,
+    /// Values for `{{KEY}}` placeholders in default values of arguments. Keys that
+    /// aren't in here are taken from environment variables.
+    /// ```
+    /// # use klask::Settings;
+    /// let mut settings = Settings::default();
+    /// settings.template_vars.insert("PROJECT".into(), "klask".into());
+    /// ```
+    pub template_vars: HashMap<String, String>,
 
     /// Override builtin strings. By default everything is in english.
     pub localization: Localization,
+    /// Replace `localization` with builtin translations for the language set in the
+    /// `LC_ALL` or `LANG` environment variables, see [`Localization::default_for_locale`].
+    /// Default is `false`.
+    pub auto_detect_locale: bool,
 
     /// Egui style used in GUI.
     pub style: Style,
+
+    /// Pass Some to override the colors of `style` with a preset. The theme can
+    /// then also be changed from a menu in the GUI.
+    pub theme: Option<Theme>,
+    /// Dense layout with less spacing and smaller widgets, applied on top of `style`.
+    /// Arguments are shown on one row with their name instead of in two columns, and
+    /// the descriptions of commands and tabs are hidden. Default is `false`.
+    pub compact_mode: bool,
+    /// Show a checkbox in the view menu for turning `compact_mode` on and off.
+    /// Default is `false`.
+    pub enable_compact_toggle: bool,
+}
+
+impl Settings {
+    /// Starts a [`SettingsBuilder`] with the default settings.
+    pub fn builder() -> SettingsBuilder {
+        SettingsBuilder::new()
+    }
+
+    /// Whether this process was relaunched by the GUI to run the closure of
+    /// [`crate::run_app`]. Useful for skipping setup that only the GUI needs.
+    pub fn is_child_process(&self) -> bool {
+        std::env::var_os(self.child_env_var()).is_some()
+    }
+
+    /// Options of the window opened by [`crate::run_app`]
+    pub(crate) fn native_options(&self) -> eframe::NativeOptions {
+        let mut options = eframe::NativeOptions::default();
+        if self.window_always_on_top {
+            options.viewport = options.viewport.with_always_on_top();
+        }
+        options
+    }
+
+    /// [`Settings::child_env_var`] or the default
+    pub(crate) fn child_env_var(&self) -> &str {
+        self.child_env_var
+            .as_deref()
+            .unwrap_or(crate::CHILD_APP_ENV_VAR)
+    }
+
+    /// [`Settings::max_concurrent_runs`], at least 1 and only 1 when running in process
+    pub(crate) fn max_concurrent_runs(&self, in_process: bool) -> usize {
+        match in_process {
+            true => 1,
+            false => self.max_concurrent_runs.max(1),
+        }
+    }
+
+    /// Command of a relaunched binary. `step` is the value of the environment
+    /// variable, the index into `run_sequence` plus one. Unknown steps use `app`.
+    pub(crate) fn child_command(&self, app: Command, step: &str) -> (usize, Command) {
+        let step: usize = step.parse().unwrap_or(0);
+        match step.checked_sub(1).and_then(|i| self.run_sequence.get(i)) {
+            Some(command) => (step, command()),
+            None => (step, app),
+        }
+    }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             enable_env: Option::default(),
+            env_default: vec![],
+            known_env_vars: vec![],
+            env_extra_ui: vec![],
             enable_stdin: Option::default(),
+            stdin_default: None,
+            stdin_extra_ui: vec![],
+            stdin_description_placeholder: None,
+            stdin_max_bytes: None,
             enable_working_dir: Option::default(),
+            working_dir_default: None,
+            working_dir_read_only: false,
+            default_tab: DefaultTab::Arguments,
+            extra_tabs: vec![],
             custom_font: Option::default(),
+            disable_file_dialogs: false,
+            enable_env_paste: false,
+            env_paste_filter: None,
+            usage_stats_path: Option::default(),
+            field_history_path: None,
+            inline_validation_errors: true,
+            run_button_ready_color: None,
+            inline_long_help: false,
+            arg_sort: ArgSort::Definition,
+            arg_order: vec![],
+            conditional_visibility: vec![],
+            error_color: Color32::RED,
+            warning_color: Color32::YELLOW,
+            validators: HashMap::new(),
+            enable_run_notes: false,
+            persist_notes: false,
+            compress_old_output: false,
+            clear_output_on_run: true,
+            output_line_separator: "\n---\n".into(),
+            run_separator_timestamp: false,
+            hide_output_on_run: false,
+            output_max_lines_before_compress: 10_000,
+            expected_output_lines: None,
+            output_strip_ansi: true,
+            output_binary_as_hex: false,
+            output_color_map: HashMap::new(),
+            output_highlight_case_sensitive: false,
+            enable_copy_json: false,
+            enable_output_popout: false,
+            enable_go_to_missing: false,
+            enable_changes_summary: false,
+            enable_detach: false,
+            shell_suffix: None,
+            enable_field_errors: false,
+            log_file: None,
+            output_scroll_behavior: ScrollBehavior::AlwaysBottom,
+            enable_save_output: false,
+            output_file_extension: "txt".into(),
+            max_concurrent_runs: 1,
+            run_sequence: vec![],
+            child_env_var: None,
+            batch_arg: None,
+            #[cfg(feature = "randomize")]
+            enable_randomize: false,
+            #[cfg(feature = "print-args")]
+            enable_print_args: false,
+            #[cfg(feature = "man")]
+            enable_man_page: false,
+            confirm_close_while_running: false,
+            kill_on_close: true,
+            show_help_panel: false,
+            subcommand_display: SubcommandDisplay::default(),
+            on_window_close: None,
+            window_always_on_top: false,
+            combine_short_flags: false,
+            infer_path_from_name: false,
+            repaint_interval: Some(Duration::from_millis(100)),
+            running_indicator: RunningIndicator::default(),
+            show_run_status: false,
+            template_vars: HashMap::new(),
             localization: Default::default(),
+            auto_detect_locale: false,
             style: Style {
                 spacing: Spacing {
                     text_edit_width: f32::MAX,
@@ -53,20 +526,469 @@ impl Default for Settings {
                 },
                 ..Default::default()
             },
+            theme: None,
+            compact_mode: false,
+            enable_compact_toggle: false,
         }
     }
 }
 
+/// Builds [`Settings`] by chaining methods, starting from [`Settings::default`].
+/// Settings without a method can still be set on the result of `build`.
+/// ```
+/// # use klask::Settings;
+/// let settings = Settings::builder()
+///     .env("Description")
+///     .stdin("")
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SettingsBuilder {
+    settings: Settings,
+}
+
+impl SettingsBuilder {
+    /// Starts with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables the environment variables tab, see [`Settings::enable_env`].
+    pub fn env(mut self, desc: impl Into<String>) -> Self {
+        self.settings.enable_env = Some(desc.into());
+        self
+    }
+
+    /// Enables the stdin tab, see [`Settings::enable_stdin`].
+    pub fn stdin(mut self, desc: impl Into<String>) -> Self {
+        self.settings.enable_stdin = Some(desc.into());
+        self
+    }
+
+    /// Enables the working directory tab, see [`Settings::enable_working_dir`].
+    pub fn working_dir(mut self, desc: impl Into<String>) -> Self {
+        self.settings.enable_working_dir = Some(desc.into());
+        self
+    }
+
+    /// Overrides builtin strings, see [`Settings::localization`].
+    pub fn localization(mut self, localization: Localization) -> Self {
+        self.settings.localization = localization;
+        self
+    }
+
+    /// Uses a custom font in the GUI, see [`Settings::custom_font`].
+    pub fn custom_font(mut self, data: Cow<'static, [u8]>) -> Self {
+        self.settings.custom_font = Some(data);
+        self
+    }
+
+    /// Egui style used in the GUI, see [`Settings::style`].
+    pub fn style(mut self, style: Style) -> Self {
+        self.settings.style = style;
+        self
+    }
+
+    /// Overrides the colors of the style with a preset, see [`Settings::theme`].
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.settings.theme = Some(theme);
+        self
+    }
+
+    /// Returns the settings.
+    pub fn build(self) -> Settings {
+        self.settings
+    }
+}
+
+/// Custom widgets for the environment variables, see [`Settings::env_extra_ui`].
+/// Clones share the same closure, so the settings can stay [`Clone`].
+#[derive(Clone)]
+pub struct EnvExtraUi(Arc<EnvExtraUiFn>);
+
+type EnvExtraUiFn = dyn Fn(&mut egui::Ui, &mut Vec<(String, String)>) + Send + Sync;
+
+impl EnvExtraUi {
+    /// Wraps the closure, it gets the variables as name and value pairs.
+    pub fn new(
+        f: impl Fn(&mut egui::Ui, &mut Vec<(String, String)>) + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn ui(&self, ui: &mut egui::Ui, env: &mut Vec<(String, String)>) {
+        (self.0)(ui, env);
+    }
+}
+
+impl fmt::Debug for EnvExtraUi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EnvExtraUi").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for EnvExtraUi {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Custom widgets for stdin, see [`Settings::stdin_extra_ui`].
+/// Clones share the same closure, so the settings can stay [`Clone`].
+#[derive(Clone)]
+pub struct StdinExtraUi(Arc<StdinExtraUiFn>);
+
+type StdinExtraUiFn = dyn Fn(&mut egui::Ui, &mut String) + Send + Sync;
+
+impl StdinExtraUi {
+    /// Wraps the closure, it gets the text or the path of the picked file.
+    pub fn new(f: impl Fn(&mut egui::Ui, &mut String) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn ui(&self, ui: &mut egui::Ui, stdin: &mut String) {
+        (self.0)(ui, stdin);
+    }
+}
+
+impl fmt::Debug for StdinExtraUi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StdinExtraUi").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for StdinExtraUi {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Contents of a tab from [`Settings::extra_tabs`].
+pub trait TabContent: Send + 'static {
+    /// Text of the tab button.
+    fn label(&self) -> &str;
+    /// Shows the contents of the tab.
+    fn ui(&mut self, ui: &mut egui::Ui);
+}
+
+/// A [`TabContent`] for [`Settings::extra_tabs`]. Clones share the same content,
+/// so the settings can stay [`Clone`].
+#[derive(Clone)]
+pub struct ExtraTab(pub(crate) Arc<Mutex<dyn TabContent>>);
+
+impl ExtraTab {
+    /// Wraps the content of a tab.
+    pub fn new(content: impl TabContent) -> Self {
+        Self(Arc::new(Mutex::new(content)))
+    }
+
+    pub(crate) fn label(&self) -> String {
+        self.0.lock().unwrap().label().to_string()
+    }
+
+    pub(crate) fn ui(&self, ui: &mut egui::Ui) {
+        self.0.lock().unwrap().ui(ui);
+    }
+}
+
+impl fmt::Debug for ExtraTab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ExtraTab").field(&self.label()).finish()
+    }
+}
+
+impl PartialEq for ExtraTab {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Closure for [`Settings::on_window_close`]. Clones share the same closure,
+/// so the settings can stay [`Clone`].
+#[derive(Clone)]
+pub struct WindowCloseHook(Arc<dyn Fn() + Send + Sync>);
+
+impl WindowCloseHook {
+    /// Wraps the closure.
+    pub fn new(f: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn call(&self) {
+        (self.0)();
+    }
+}
+
+impl fmt::Debug for WindowCloseHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WindowCloseHook").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for WindowCloseHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Check for an argument value, see [`Settings::validators`]. Clones share the same
+/// closure, so the settings can stay [`Clone`].
+#[derive(Clone)]
+pub struct Validator(Arc<ValidatorFn>);
+
+type ValidatorFn = dyn Fn(&str) -> Result<(), String> + Send + Sync;
+
+impl Validator {
+    /// Wraps the closure, an `Err` is the message shown on the argument.
+    pub fn new(f: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn call(&self, value: &str) -> Result<(), String> {
+        (self.0)(value)
+    }
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Validator").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Validator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Order of the arguments in the GUI, see [`Settings::arg_sort`]. Only the display
+/// changes, positional arguments are still passed in their order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ArgSort {
+    /// The order they are defined in.
+    #[default]
+    Definition,
+    /// By name.
+    Alphabetical,
+    /// Required arguments, then optional ones, otherwise in definition order.
+    RequiredFirst,
+    /// Optional arguments, then required ones, otherwise in definition order.
+    OptionalFirst,
+}
+
+/// Tab shown first, see [`Settings::default_tab`]. The working directory is part of
+/// the arguments tab.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DefaultTab {
+    /// The form of the command
+    #[default]
+    Arguments,
+    /// Needs [`Settings::enable_env`]
+    Env,
+    /// Needs [`Settings::enable_stdin`]
+    Stdin,
+}
+
+/// How subcommands are picked, see [`Settings::subcommand_display`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SubcommandDisplay {
+    /// A row of buttons, or a searchable dropdown when there are many.
+    #[default]
+    Dropdown,
+    /// A list of buttons left of the form of the selected subcommand, for commands
+    /// with many subcommands like `cargo`.
+    SidePanel,
+}
+
+/// How a run in progress is shown, see [`Settings::running_indicator`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RunningIndicator {
+    /// [`Localization::running`] followed by animated dots, like "Running..".
+    #[default]
+    Dots,
+    /// A spinner before [`Localization::running`].
+    Spinner,
+    /// [`Localization::running`] followed by the time since the run started,
+    /// like "Running 00:12". Useful for long jobs.
+    Elapsed,
+}
+
+/// Scrolling of the output while a program runs, see [`Settings::output_scroll_behavior`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ScrollBehavior {
+    /// Follow the newest output.
+    #[default]
+    AlwaysBottom,
+    /// Keep the first line in view, for programs that print the most important things first.
+    AlwaysTop,
+    /// Never scroll automatically.
+    UserControlled,
+}
+
+/// Color presets for the GUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Theme {
+    /// Default egui dark colors.
+    Dark,
+    /// Default egui light colors.
+    Light,
+    /// White on black with thicker outlines, including for errors.
+    HighContrast,
+}
+
+impl Theme {
+    pub(crate) const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::HighContrast];
+
+    /// Replaces the visuals of `style`, keeping everything else (like spacing).
+    pub fn apply(self, mut style: Style) -> Style {
+        style.visuals = match self {
+            Theme::Dark => Visuals::dark(),
+            Theme::Light => Visuals::light(),
+            Theme::HighContrast => {
+                let mut visuals = Visuals::dark();
+                visuals.override_text_color = Some(Color32::WHITE);
+                visuals.panel_fill = Color32::BLACK;
+                visuals.window_fill = Color32::BLACK;
+                visuals.extreme_bg_color = Color32::BLACK;
+                visuals.widgets.noninteractive.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.widgets.inactive.bg_stroke = Stroke::new(2.0, Color32::LIGHT_GRAY);
+                visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.widgets.active.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals
+            }
+        };
+        style
+    }
+
+    pub(crate) fn name(self, localization: &Localization) -> &str {
+        match self {
+            Theme::Dark => &localization.theme_dark,
+            Theme::Light => &localization.theme_light,
+            Theme::HighContrast => &localization.theme_high_contrast,
+        }
+    }
+}
+
+/// Reduces the spacing and widget sizes of `style`, see [`Settings::compact_mode`].
+pub(crate) fn compact_style(mut style: Style) -> Style {
+    style.spacing.item_spacing = egui::vec2(2.0, 2.0);
+    style.spacing.button_padding = egui::vec2(4.0, 1.0);
+    style.spacing.interact_size.y = 16.0;
+    style
+}
+
+/// Names and String fields of a [`Localization`], borrowed with `&` or `&mut`.
+/// The tuple fields are left out.
+macro_rules! string_fields {
+    ($localization:expr, $($borrow:tt)+) => {
+        [
+            ("optional", $($borrow)+ $localization.optional),
+            ("value_empty", $($borrow)+ $localization.value_empty),
+            ("value_default", $($borrow)+ $localization.value_default),
+            ("select_file", $($borrow)+ $localization.select_file),
+            ("select_directory", $($borrow)+ $localization.select_directory),
+            ("env_paste", $($borrow)+ $localization.env_paste),
+            ("field_history", $($borrow)+ $localization.field_history),
+            ("new_value", $($borrow)+ $localization.new_value),
+            ("reset", $($borrow)+ $localization.reset),
+            ("reset_to_default", $($borrow)+ $localization.reset_to_default),
+            ("error_invalid_email", $($borrow)+ $localization.error_invalid_email),
+            ("warning_unresolved_template", $($borrow)+ $localization.warning_unresolved_template),
+            ("arguments", $($borrow)+ $localization.arguments),
+            ("env_variables", $($borrow)+ $localization.env_variables),
+            ("error_env_var_cant_be_empty", $($borrow)+ $localization.error_env_var_cant_be_empty),
+            ("env_var_known", $($borrow)+ $localization.env_var_known),
+            ("env_var_unknown", $($borrow)+ $localization.env_var_unknown),
+            ("env_var_sets_arg", $($borrow)+ $localization.env_var_sets_arg),
+            ("env_var_value_from", $($borrow)+ $localization.env_var_value_from),
+            ("input", $($borrow)+ $localization.input),
+            ("stdin_placeholder", $($borrow)+ $localization.stdin_placeholder),
+            ("text", $($borrow)+ $localization.text),
+            ("file", $($borrow)+ $localization.file),
+            ("stdin_size_warning", $($borrow)+ $localization.stdin_size_warning),
+            ("stdin_from_output", $($borrow)+ $localization.stdin_from_output),
+            ("working_directory", $($borrow)+ $localization.working_directory),
+            ("working_dir_missing", $($borrow)+ $localization.working_dir_missing),
+            ("run", $($borrow)+ $localization.run),
+            ("confirm_close_while_running", $($borrow)+ $localization.confirm_close_while_running),
+            ("close_while_running", $($borrow)+ $localization.close_while_running),
+            ("kill_and_close", $($borrow)+ $localization.kill_and_close),
+            ("keep_running_and_close", $($borrow)+ $localization.keep_running_and_close),
+            ("cancel", $($borrow)+ $localization.cancel),
+            ("copy_as_json", $($borrow)+ $localization.copy_as_json),
+            ("man_page", $($borrow)+ $localization.man_page),
+            ("generate_man_page", $($borrow)+ $localization.generate_man_page),
+            ("copy_man_page", $($borrow)+ $localization.copy_man_page),
+            ("randomize", $($borrow)+ $localization.randomize),
+            ("optional_bool_hint", $($borrow)+ $localization.optional_bool_hint),
+            ("other_subcommand", $($borrow)+ $localization.other_subcommand),
+            ("changes", $($borrow)+ $localization.changes),
+            ("no_changes", $($borrow)+ $localization.no_changes),
+            ("kill", $($borrow)+ $localization.kill),
+            ("send_signal", $($borrow)+ $localization.send_signal),
+            ("running", $($borrow)+ $localization.running),
+            ("ready", $($borrow)+ $localization.ready),
+            ("finished", $($borrow)+ $localization.finished),
+            ("exit_code", $($borrow)+ $localization.exit_code),
+            ("process_exited", $($borrow)+ $localization.process_exited),
+            ("expand_output", $($borrow)+ $localization.expand_output),
+            ("save_output", $($borrow)+ $localization.save_output),
+            ("saved_to", $($borrow)+ $localization.saved_to),
+            ("pop_out_output", $($borrow)+ $localization.pop_out_output),
+            ("load_earlier_output", $($borrow)+ $localization.load_earlier_output),
+            ("earlier_output", $($borrow)+ $localization.earlier_output),
+            ("output_done", $($borrow)+ $localization.output_done),
+            ("more_output_than_expected", $($borrow)+ $localization.more_output_than_expected),
+            ("line_numbers", $($borrow)+ $localization.line_numbers),
+            ("wrap_lines", $($borrow)+ $localization.wrap_lines),
+            ("detach", $($borrow)+ $localization.detach),
+            ("go_to_missing", $($borrow)+ $localization.go_to_missing),
+            ("select_subcommand_required", $($borrow)+ $localization.select_subcommand_required),
+            ("output", $($borrow)+ $localization.output),
+            ("usage_stats", $($borrow)+ $localization.usage_stats),
+            ("notes", $($borrow)+ $localization.notes),
+            ("view", $($borrow)+ $localization.view),
+            ("compact", $($borrow)+ $localization.compact),
+            ("theme", $($borrow)+ $localization.theme),
+            ("theme_dark", $($borrow)+ $localization.theme_dark),
+            ("theme_light", $($borrow)+ $localization.theme_light),
+            ("theme_high_contrast", $($borrow)+ $localization.theme_high_contrast),
+            ("about", $($borrow)+ $localization.about),
+            ("help_panel", $($borrow)+ $localization.help_panel),
+            ("help_panel_empty", $($borrow)+ $localization.help_panel_empty),
+            ("version", $($borrow)+ $localization.version),
+            ("author", $($borrow)+ $localization.author),
+        ]
+    };
+}
+
 /// Localization for builtin strings.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct Localization {
     /// Displays when the value is optional. Default is "(Optional)".
     pub optional: String,
+    /// Summary of an argument without a value, like in the list of changes. Default is "(empty)".
+    pub value_empty: String,
+    /// Summary of an argument left at its default. Default is "(default)".
+    pub value_default: String,
     /// Button text for opening a dialog for file selection. Default is "Select file...".
     pub select_file: String,
     /// Button text for opening a dialog for directory selection. Default is "Select directory...".
     pub select_directory: String,
+    /// Button for filling in the value of an environment variable, see
+    /// [`Settings::enable_env_paste`]. Default is "Env".
+    pub env_paste: String,
+    /// Hover text of the button listing previously entered values, see
+    /// [`Settings::field_history_path`]. Default is "Previous values, Delete removes the hovered one".
+    pub field_history: String,
     /// Button text for creating a new field for multi-value arguments and environment variables. Default is "New value".
     pub new_value: String,
     /// Button text for resetting multi-value arguments. Default is "Reset".
@@ -76,6 +998,25 @@ pub struct Localization {
     /// Error text when an argument is requires. The argument name will be displayed between the two strings.
     /// Default is ("Argument '", "' is required").
     pub error_is_required: (String, String),
+    /// Hover text for arguments disabled because a conflicting argument is set.
+    /// The other argument will be displayed between the two strings.
+    /// Default is ("Disabled because ", " is set").
+    pub disabled_by_conflict: (String, String),
+    /// Error text when an argument has fewer values than `num_args` allows.
+    /// The argument name and the minimum will be displayed between the strings.
+    /// Default is ("Argument '", "' requires at least ", " values").
+    pub error_too_few_values: (String, String, String),
+    /// Error text when an argument has more values than `num_args` allows.
+    /// The argument name and the maximum will be displayed between the strings.
+    /// Default is ("Argument '", "' allows at most ", " values").
+    pub error_too_many_values: (String, String, String),
+    /// Error text when hovering over a malformed email address.
+    /// Default is "Not a valid email address".
+    pub error_invalid_email: String,
+    /// Warning shown when hovering over an argument whose default value has `{{KEY}}`
+    /// placeholders that couldn't be resolved. The keys are displayed after it.
+    /// Default is "Unresolved template variables: ".
+    pub warning_unresolved_template: String,
     /// Text for the arguments tab. Default is "Arguments".
     pub arguments: String,
     /// Text for the environment variables tab. Default is "Environment variables".
@@ -83,43 +1024,320 @@ pub struct Localization {
     /// Error displayed when user tries to pass an environment variable with no name.
     /// Default is "Environment variable can't be empty".
     pub error_env_var_cant_be_empty: String,
+    /// Hover text for environment variables read by the program. Default is "Read by the program".
+    pub env_var_known: String,
+    /// Hover text for environment variables not known to be read by the program.
+    /// Default is "Not read by the program".
+    pub env_var_unknown: String,
+    /// Hover text for arguments that can be set with an environment variable.
+    /// The variable will be displayed after it. Default is "Can also be set with the environment variable ".
+    pub env_var_sets_arg: String,
+    /// Hover text for arguments filled from an environment variable.
+    /// The variable will be displayed after it. Default is "Value taken from the environment variable ".
+    pub env_var_value_from: String,
     /// Text for the input tab. Default is "Input".
     pub input: String,
+    /// Hint inside the empty stdin text editor, unless
+    /// [`Settings::stdin_description_placeholder`] is set. Default is "Text passed to standard input".
+    pub stdin_placeholder: String,
     /// Text for the button when user wants to write text for input in the input tab. Default is "Text".
     pub text: String,
     /// Text for the button when user wants to select file for input in the input tab. Default is "File".
     pub file: String,
+    /// Shown when stdin is larger than [`Settings::stdin_max_bytes`]. `{current}` and
+    /// `{max}` are replaced with the sizes. Default is "Input exceeds limit ({current} / {max} bytes)".
+    pub stdin_size_warning: String,
+    /// Button for using the output of the last run as the input text. Default is "Use last output".
+    pub stdin_from_output: String,
     /// Text displayed as a hint for the working directory field. Default is "Working directory".
     pub working_directory: String,
+    /// Shown when hovering over a default working directory that doesn't exist.
+    /// Default is "Directory doesn't exist".
+    pub working_dir_missing: String,
     /// Button text for running the binary. Default is "Run".
     pub run: String,
+    /// Title of the dialog shown by [`Settings::confirm_close_while_running`]. Default is "Close the window?".
+    pub confirm_close_while_running: String,
+    /// Message of the dialog shown when closing the window during a run.
+    /// Default is "The program is still running".
+    pub close_while_running: String,
+    /// Button text for killing running programs and closing the window. Default is "Kill and close".
+    pub kill_and_close: String,
+    /// Button text for closing the window and letting running programs continue.
+    /// Default is "Keep running and close".
+    pub keep_running_and_close: String,
+    /// Button text for cancelling a dialog. Default is "Cancel".
+    pub cancel: String,
+    /// Button text for copying the arguments as a JSON object, see
+    /// [`Settings::enable_copy_json`]. Default is "Copy as JSON".
+    pub copy_as_json: String,
+    /// Menu for generating a man page, see [`Settings::enable_man_page`].
+    /// Default is "Man page".
+    pub man_page: String,
+    /// Button for saving the man page to a file. Default is "Save man page…".
+    pub generate_man_page: String,
+    /// Button for copying the man page. Default is "Copy roff source".
+    pub copy_man_page: String,
+    /// Button text for filling the arguments with random values, see
+    /// `Settings::enable_randomize`. Default is "🎲 Randomize".
+    pub randomize: String,
+    /// Shown when hovering over a checkbox for a flag that can also be left out.
+    /// Default is "Click to switch between unset (-), on and off".
+    pub optional_bool_hint: String,
+    /// Option for typing the name of a subcommand that isn't listed, shown for
+    /// commands with [`clap::Command::allow_external_subcommands`]. Default is "Other".
+    pub other_subcommand: String,
+    /// Title of the summary of arguments changed from their defaults, see
+    /// [`Settings::enable_changes_summary`]. Default is "Changes".
+    pub changes: String,
+    /// Shown in the summary when no argument was changed. Default is "No changes".
+    pub no_changes: String,
     /// Button text for killing the binary. Default is "Kill".
     pub kill: String,
-    /// Text that shows when the binary is running. There will be animated dots ("...") displayed after it.
-    /// Default is "Running".
+    /// Menu for sending a signal to the running binary, only on unix. Default is "Send signal".
+    pub send_signal: String,
+    /// Text that shows when the binary is running, with the indicator picked with
+    /// [`Settings::running_indicator`]. Default is "Running".
     pub running: String,
+    /// Shown before the first run with [`Settings::show_run_status`]. Default is "Ready".
+    pub ready: String,
+    /// Shown once a run is done with [`Settings::show_run_status`]. Default is "Finished".
+    pub finished: String,
+    /// Text shown before the exit code of a run when multiple runs are enabled. Default is "Exit code".
+    pub exit_code: String,
+    /// Summary shown instead of the output with [`Settings::hide_output_on_run`].
+    /// `{code}` is replaced with the exit code. Default is "Process exited (code {code})".
+    pub process_exited: String,
+    /// Button for showing the output hidden by [`Settings::hide_output_on_run`].
+    /// Default is "Expand output".
+    pub expand_output: String,
+    /// Button for saving the output with [`Settings::enable_save_output`].
+    /// Default is "Save output…".
+    pub save_output: String,
+    /// Shown for a few seconds after saving the output. `{path}` is replaced with
+    /// the path of the file. Default is "Saved to {path}".
+    pub saved_to: String,
+    /// Button for showing the output in its own window, see
+    /// [`Settings::enable_output_popout`]. Default is "Pop out output".
+    pub pop_out_output: String,
+    /// Button for viewing output moved out by [`Settings::compress_old_output`].
+    /// Default is "Load earlier output".
+    pub load_earlier_output: String,
+    /// Title of the window opened by `load_earlier_output`. Default is "Earlier output".
+    pub earlier_output: String,
+    /// Shown in place of the progress bar of [`Settings::expected_output_lines`] once the
+    /// program exits. `{lines}` is replaced with the number of lines. Default is "Done ({lines} lines)".
+    pub output_done: String,
+    /// Warning when the program prints more lines than expected. Default is "More output than expected".
+    pub more_output_than_expected: String,
+    /// Checkbox for numbering the output lines. Default is "Line numbers".
+    pub line_numbers: String,
+    /// Checkbox for wrapping long output lines. Default is "Wrap lines".
+    pub wrap_lines: String,
+    /// Button for closing the window and letting the program run in the background,
+    /// see [`Settings::enable_detach`]. Default is "Detach".
+    pub detach: String,
+    /// Button for scrolling to the first required argument without a value, see
+    /// [`Settings::enable_go_to_missing`]. Default is "Go to missing field".
+    pub go_to_missing: String,
+    /// Shown instead of running when a command with `subcommand_required` has none
+    /// selected. Default is "Select a subcommand to continue".
+    pub select_subcommand_required: String,
+    /// Title of the window with the popped out output, after the name of the program.
+    /// Default is "Output".
+    pub output: String,
+    /// Button text and window title for argument usage stats. Default is "ℹ Usage stats".
+    pub usage_stats: String,
+    /// Label for the run notes field. Default is "Notes".
+    pub notes: String,
+    /// Text for the menu for showing and hiding the environment variables, input and
+    /// working directory. Default is "View".
+    pub view: String,
+    /// Checkbox in the view menu for [`Settings::compact_mode`]. Default is "Compact".
+    pub compact: String,
+    /// Text for the theme menu. Default is "Theme".
+    pub theme: String,
+    /// Name of [`Theme::Dark`]. Default is "Dark".
+    pub theme_dark: String,
+    /// Name of [`Theme::Light`]. Default is "Light".
+    pub theme_light: String,
+    /// Name of [`Theme::HighContrast`]. Default is "High contrast".
+    pub theme_high_contrast: String,
+    /// Text for the menu with the name, version, author and about of the program.
+    /// Default is "About".
+    pub about: String,
+    /// Heading of the panel from [`Settings::show_help_panel`]. Default is "Help".
+    pub help_panel: String,
+    /// Shown in the help panel before a field was picked.
+    /// Default is "Click a field to see its help".
+    pub help_panel_empty: String,
+    /// Label of the version in the about menu. Default is "Version".
+    pub version: String,
+    /// Label of the author in the about menu. Default is "Author".
+    pub author: String,
+}
+
+impl Localization {
+    /// Returns builtin translations for a language given as a two-letter ISO 639-1 code.
+    /// Locales like `de_DE.UTF-8` are also accepted. Unsupported languages fall back to english.
+    ///
+    /// Supported codes are `en` (English), `de` (German), `fr` (French), `es` (Spanish),
+    /// `zh` (Simplified Chinese), `ja` (Japanese) and `ar` (Arabic). Chinese, Japanese and
+    /// Arabic need a [`Settings::custom_font`] with the right glyphs. Arabic text is laid
+    /// out left-to-right, as egui doesn't support right-to-left text.
+    /// ```
+    /// # use klask::Localization;
+    /// let localization = Localization::default_for_locale("de_DE.UTF-8");
+    /// assert_eq!(localization.run, "Ausführen");
+    /// ```
+    pub fn default_for_locale(locale: &str) -> Localization {
+        let language = locale
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        locales::for_language(&language).unwrap_or_default()
+    }
+
+    /// Language codes accepted by [`Localization::default_for_locale`].
+    pub fn supported_locales() -> Vec<&'static str> {
+        locales::SUPPORTED.to_vec()
+    }
+
+    /// Returns the names of the fields that are empty, which would show up as blank
+    /// buttons and labels. The tuple fields are not checked, as parts of them can be empty.
+    /// ```
+    /// # use klask::Localization;
+    /// let mut localization = Localization::default();
+    /// assert_eq!(localization.validate(), Ok(()));
+    /// localization.run = String::new();
+    /// assert_eq!(localization.validate(), Err(vec!["run"]));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<&'static str>> {
+        let fields: [(&'static str, &String); 76] = string_fields!(self, &);
+
+        let empty: Vec<_> = fields
+            .into_iter()
+            .filter(|(_, value)| value.is_empty())
+            .map(|(name, _)| name)
+            .collect();
+
+        match empty.is_empty() {
+            true => Ok(()),
+            false => Err(empty),
+        }
+    }
+
+    /// The String fields with their names, for [`Settings::from_config_file`].
+    #[cfg(feature = "config-file")]
+    pub(crate) fn string_fields_mut(&mut self) -> [(&'static str, &mut String); 76] {
+        string_fields!(self, &mut)
+    }
+
+    /// Translations for the locale set in the environment, if any is set.
+    pub(crate) fn from_env() -> Option<Localization> {
+        ["LC_ALL", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+            .map(|locale| Self::default_for_locale(&locale))
+    }
 }
 
 impl Default for Localization {
     fn default() -> Self {
         Self {
             optional: "(Optional)".into(),
+            value_empty: "(empty)".into(),
+            value_default: "(default)".into(),
             select_file: "Select file...".into(),
             select_directory: "Select directory...".into(),
+            env_paste: "Env".into(),
+            field_history: "Previous values, Delete removes the hovered one".into(),
             new_value: "New value".into(),
             reset: "Reset".into(),
             reset_to_default: "Reset to default".into(),
             error_is_required: ("Argument '".into(), "' is required".into()),
+            disabled_by_conflict: ("Disabled because ".into(), " is set".into()),
+            error_too_few_values: (
+                "Argument '".into(),
+                "' requires at least ".into(),
+                " values".into(),
+            ),
+            error_too_many_values: (
+                "Argument '".into(),
+                "' allows at most ".into(),
+                " values".into(),
+            ),
+            error_invalid_email: "Not a valid email address".into(),
+            warning_unresolved_template: "Unresolved template variables: ".into(),
             arguments: "Arguments".into(),
             env_variables: "Environment variables".into(),
             error_env_var_cant_be_empty: "Environment variable can't be empty".into(),
+            env_var_known: "Read by the program".into(),
+            env_var_unknown: "Not read by the program".into(),
+            env_var_sets_arg: "Can also be set with the environment variable ".into(),
+            env_var_value_from: "Value taken from the environment variable ".into(),
             input: "Input".into(),
+            stdin_placeholder: "Text passed to standard input".into(),
             text: "Text".into(),
             file: "File".into(),
+            stdin_size_warning: "Input exceeds limit ({current} / {max} bytes)".into(),
+            stdin_from_output: "Use last output".into(),
             working_directory: "Working directory".into(),
+            working_dir_missing: "Directory doesn't exist".into(),
             run: "Run".into(),
+            confirm_close_while_running: "Close the window?".into(),
+            close_while_running: "The program is still running".into(),
+            kill_and_close: "Kill and close".into(),
+            keep_running_and_close: "Keep running and close".into(),
+            cancel: "Cancel".into(),
+            copy_as_json: "Copy as JSON".into(),
+            man_page: "Man page".into(),
+            generate_man_page: "Save man page…".into(),
+            copy_man_page: "Copy roff source".into(),
+            randomize: "🎲 Randomize".into(),
+            optional_bool_hint: "Click to switch between unset (-), on and off".into(),
+            other_subcommand: "Other".into(),
+            changes: "Changes".into(),
+            no_changes: "No changes".into(),
             kill: "Kill".into(),
+            send_signal: "Send signal".into(),
             running: "Running".into(),
+            ready: "Ready".into(),
+            finished: "Finished".into(),
+            exit_code: "Exit code".into(),
+            process_exited: "Process exited (code {code})".into(),
+            expand_output: "Expand output".into(),
+            save_output: "Save output…".into(),
+            saved_to: "Saved to {path}".into(),
+            pop_out_output: "Pop out output".into(),
+            load_earlier_output: "Load earlier output".into(),
+            earlier_output: "Earlier output".into(),
+            output_done: "Done ({lines} lines)".into(),
+            more_output_than_expected: "More output than expected".into(),
+            line_numbers: "Line numbers".into(),
+            wrap_lines: "Wrap lines".into(),
+            detach: "Detach".into(),
+            go_to_missing: "Go to missing field".into(),
+            select_subcommand_required: "Select a subcommand to continue".into(),
+            output: "Output".into(),
+            usage_stats: "ℹ Usage stats".into(),
+            notes: "Notes".into(),
+            view: "View".into(),
+            compact: "Compact".into(),
+            theme: "Theme".into(),
+            theme_dark: "Dark".into(),
+            theme_light: "Light".into(),
+            theme_high_contrast: "High contrast".into(),
+            about: "About".into(),
+            help_panel: "Help".into(),
+            help_panel_empty: "Click a field to see its help".into(),
+            version: "Version".into(),
+            author: "Author".into(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
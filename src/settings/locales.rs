@@ -0,0 +1,569 @@
+//! Builtin translations. Fields that aren't translated fall back to english.
+
+use super::Localization;
+
+pub const SUPPORTED: [&str; 7] = ["en", "de", "fr", "es", "zh", "ja", "ar"];
+
+/// Takes a two-letter ISO 639-1 language code.
+pub fn for_language(language: &str) -> Option<Localization> {
+    match language {
+        "en" => Some(Localization::default()),
+        "de" => Some(german()),
+        "fr" => Some(french()),
+        "es" => Some(spanish()),
+        "zh" => Some(chinese()),
+        "ja" => Some(japanese()),
+        "ar" => Some(arabic()),
+        _ => None,
+    }
+}
+
+fn german() -> Localization {
+    Localization {
+        optional: "(Optional)".into(),
+        value_empty: "(leer)".into(),
+        value_default: "(Standard)".into(),
+        select_file: "Datei auswählen...".into(),
+        select_directory: "Verzeichnis auswählen...".into(),
+        env_paste: "Umgebung".into(),
+        field_history: "Frühere Werte, Entf entfernt den markierten".into(),
+        new_value: "Neuer Wert".into(),
+        reset: "Zurücksetzen".into(),
+        reset_to_default: "Auf Standard zurücksetzen".into(),
+        error_is_required: ("Argument '".into(), "' ist erforderlich".into()),
+        disabled_by_conflict: ("Deaktiviert, weil ".into(), " gesetzt ist".into()),
+        error_too_few_values: (
+            "Argument '".into(),
+            "' benötigt mindestens ".into(),
+            " Werte".into(),
+        ),
+        error_too_many_values: (
+            "Argument '".into(),
+            "' erlaubt höchstens ".into(),
+            " Werte".into(),
+        ),
+        error_invalid_email: "Keine gültige E-Mail-Adresse".into(),
+        warning_unresolved_template: "Nicht aufgelöste Vorlagenvariablen: ".into(),
+        arguments: "Argumente".into(),
+        env_variables: "Umgebungsvariablen".into(),
+        error_env_var_cant_be_empty: "Umgebungsvariable darf nicht leer sein".into(),
+        env_var_known: "Wird vom Programm gelesen".into(),
+        env_var_unknown: "Wird vom Programm nicht gelesen".into(),
+        env_var_sets_arg: "Kann auch mit der Umgebungsvariable gesetzt werden: ".into(),
+        env_var_value_from: "Wert aus der Umgebungsvariable: ".into(),
+        input: "Eingabe".into(),
+        stdin_placeholder: "Text für die Standardeingabe".into(),
+        text: "Text".into(),
+        file: "Datei".into(),
+        stdin_size_warning: "Eingabe überschreitet das Limit ({current} / {max} Bytes)".into(),
+        stdin_from_output: "Letzte Ausgabe verwenden".into(),
+        working_directory: "Arbeitsverzeichnis".into(),
+        working_dir_missing: "Verzeichnis existiert nicht".into(),
+        run: "Ausführen".into(),
+        confirm_close_while_running: "Fenster schließen?".into(),
+        close_while_running: "Das Programm läuft noch".into(),
+        kill_and_close: "Beenden und schließen".into(),
+        keep_running_and_close: "Weiterlaufen lassen und schließen".into(),
+        cancel: "Abbrechen".into(),
+        copy_as_json: "Als JSON kopieren".into(),
+        man_page: "Manpage".into(),
+        generate_man_page: "Manpage speichern…".into(),
+        copy_man_page: "roff-Quelltext kopieren".into(),
+        randomize: "🎲 Zufallswerte".into(),
+        optional_bool_hint: "Klicken, um zwischen nicht gesetzt (-), an und aus zu wechseln".into(),
+        other_subcommand: "Andere".into(),
+        changes: "Änderungen".into(),
+        no_changes: "Keine Änderungen".into(),
+        kill: "Beenden".into(),
+        send_signal: "Signal senden".into(),
+        running: "Läuft".into(),
+        ready: "Bereit".into(),
+        finished: "Fertig".into(),
+        exit_code: "Exit-Code".into(),
+        process_exited: "Prozess beendet (Code {code})".into(),
+        expand_output: "Ausgabe anzeigen".into(),
+        save_output: "Ausgabe speichern…".into(),
+        saved_to: "Gespeichert unter {path}".into(),
+        pop_out_output: "Ausgabe in eigenem Fenster".into(),
+        load_earlier_output: "Frühere Ausgabe laden".into(),
+        earlier_output: "Frühere Ausgabe".into(),
+        output_done: "Fertig ({lines} Zeilen)".into(),
+        more_output_than_expected: "Mehr Ausgabe als erwartet".into(),
+        line_numbers: "Zeilennummern".into(),
+        wrap_lines: "Zeilen umbrechen".into(),
+        detach: "Abkoppeln".into(),
+        go_to_missing: "Zum fehlenden Feld".into(),
+        select_subcommand_required: "Wähle einen Unterbefehl, um fortzufahren".into(),
+        output: "Ausgabe".into(),
+        usage_stats: "ℹ Nutzungsstatistik".into(),
+        notes: "Notizen".into(),
+        view: "Ansicht".into(),
+        compact: "Kompakt".into(),
+        theme: "Design".into(),
+        theme_dark: "Dunkel".into(),
+        theme_light: "Hell".into(),
+        theme_high_contrast: "Hoher Kontrast".into(),
+        about: "Über".into(),
+        help_panel: "Hilfe".into(),
+        help_panel_empty: "Klicke auf ein Feld, um seine Hilfe zu sehen".into(),
+        version: "Version".into(),
+        author: "Autor".into(),
+        ..Localization::default()
+    }
+}
+
+fn french() -> Localization {
+    Localization {
+        optional: "(Facultatif)".into(),
+        value_empty: "(vide)".into(),
+        value_default: "(par défaut)".into(),
+        select_file: "Choisir un fichier...".into(),
+        select_directory: "Choisir un dossier...".into(),
+        env_paste: "Env".into(),
+        field_history: "Valeurs précédentes, Suppr retire celle survolée".into(),
+        new_value: "Nouvelle valeur".into(),
+        reset: "Réinitialiser".into(),
+        reset_to_default: "Rétablir les valeurs par défaut".into(),
+        error_is_required: ("L'argument '".into(), "' est obligatoire".into()),
+        disabled_by_conflict: ("Désactivé car ".into(), " est défini".into()),
+        error_too_few_values: (
+            "L'argument '".into(),
+            "' nécessite au moins ".into(),
+            " valeurs".into(),
+        ),
+        error_too_many_values: (
+            "L'argument '".into(),
+            "' accepte au plus ".into(),
+            " valeurs".into(),
+        ),
+        error_invalid_email: "Adresse e-mail non valide".into(),
+        warning_unresolved_template: "Variables de modèle non résolues : ".into(),
+        arguments: "Arguments".into(),
+        env_variables: "Variables d'environnement".into(),
+        error_env_var_cant_be_empty: "La variable d'environnement ne peut pas être vide".into(),
+        env_var_known: "Lue par le programme".into(),
+        env_var_unknown: "Non lue par le programme".into(),
+        env_var_sets_arg: "Peut aussi être définie avec la variable d'environnement ".into(),
+        env_var_value_from: "Valeur prise de la variable d'environnement ".into(),
+        input: "Entrée".into(),
+        stdin_placeholder: "Texte passé à l'entrée standard".into(),
+        text: "Texte".into(),
+        file: "Fichier".into(),
+        stdin_size_warning: "L'entrée dépasse la limite ({current} / {max} octets)".into(),
+        stdin_from_output: "Utiliser la dernière sortie".into(),
+        working_directory: "Répertoire de travail".into(),
+        working_dir_missing: "Le dossier n'existe pas".into(),
+        run: "Exécuter".into(),
+        confirm_close_while_running: "Fermer la fenêtre ?".into(),
+        close_while_running: "Le programme est toujours en cours".into(),
+        kill_and_close: "Arrêter et fermer".into(),
+        keep_running_and_close: "Laisser tourner et fermer".into(),
+        cancel: "Annuler".into(),
+        copy_as_json: "Copier en JSON".into(),
+        man_page: "Page de manuel".into(),
+        generate_man_page: "Enregistrer la page de manuel…".into(),
+        copy_man_page: "Copier la source roff".into(),
+        randomize: "🎲 Valeurs aléatoires".into(),
+        optional_bool_hint: "Cliquer pour basculer entre non défini (-), activé et désactivé"
+            .into(),
+        other_subcommand: "Autre".into(),
+        changes: "Modifications".into(),
+        no_changes: "Aucune modification".into(),
+        kill: "Arrêter".into(),
+        send_signal: "Envoyer un signal".into(),
+        running: "En cours".into(),
+        ready: "Prêt".into(),
+        finished: "Terminé".into(),
+        exit_code: "Code de sortie".into(),
+        process_exited: "Processus terminé (code {code})".into(),
+        expand_output: "Afficher la sortie".into(),
+        save_output: "Enregistrer la sortie…".into(),
+        saved_to: "Enregistré dans {path}".into(),
+        pop_out_output: "Détacher la sortie".into(),
+        load_earlier_output: "Charger la sortie précédente".into(),
+        earlier_output: "Sortie précédente".into(),
+        output_done: "Terminé ({lines} lignes)".into(),
+        more_output_than_expected: "Plus de sortie que prévu".into(),
+        line_numbers: "Numéros de ligne".into(),
+        wrap_lines: "Retour à la ligne".into(),
+        detach: "Détacher".into(),
+        go_to_missing: "Aller au champ manquant".into(),
+        select_subcommand_required: "Choisissez une sous-commande pour continuer".into(),
+        output: "Sortie".into(),
+        usage_stats: "ℹ Statistiques d'utilisation".into(),
+        notes: "Notes".into(),
+        view: "Affichage".into(),
+        compact: "Compact".into(),
+        theme: "Thème".into(),
+        theme_dark: "Sombre".into(),
+        theme_light: "Clair".into(),
+        theme_high_contrast: "Contraste élevé".into(),
+        about: "À propos".into(),
+        help_panel: "Aide".into(),
+        help_panel_empty: "Cliquez sur un champ pour voir son aide".into(),
+        version: "Version".into(),
+        author: "Auteur".into(),
+        ..Localization::default()
+    }
+}
+
+fn spanish() -> Localization {
+    Localization {
+        optional: "(Opcional)".into(),
+        value_empty: "(vacío)".into(),
+        value_default: "(predeterminado)".into(),
+        select_file: "Seleccionar archivo...".into(),
+        select_directory: "Seleccionar directorio...".into(),
+        env_paste: "Entorno".into(),
+        field_history: "Valores anteriores, Supr quita el señalado".into(),
+        new_value: "Nuevo valor".into(),
+        reset: "Restablecer".into(),
+        reset_to_default: "Restablecer valores predeterminados".into(),
+        error_is_required: ("El argumento '".into(), "' es obligatorio".into()),
+        disabled_by_conflict: ("Desactivado porque ".into(), " está definido".into()),
+        error_too_few_values: (
+            "El argumento '".into(),
+            "' requiere al menos ".into(),
+            " valores".into(),
+        ),
+        error_too_many_values: (
+            "El argumento '".into(),
+            "' admite como máximo ".into(),
+            " valores".into(),
+        ),
+        error_invalid_email: "No es una dirección de correo electrónico válida".into(),
+        warning_unresolved_template: "Variables de plantilla sin resolver: ".into(),
+        arguments: "Argumentos".into(),
+        env_variables: "Variables de entorno".into(),
+        error_env_var_cant_be_empty: "La variable de entorno no puede estar vacía".into(),
+        env_var_known: "Leída por el programa".into(),
+        env_var_unknown: "No leída por el programa".into(),
+        env_var_sets_arg: "También se puede definir con la variable de entorno ".into(),
+        env_var_value_from: "Valor tomado de la variable de entorno ".into(),
+        input: "Entrada".into(),
+        stdin_placeholder: "Texto pasado a la entrada estándar".into(),
+        text: "Texto".into(),
+        file: "Archivo".into(),
+        stdin_size_warning: "La entrada supera el límite ({current} / {max} bytes)".into(),
+        stdin_from_output: "Usar la última salida".into(),
+        working_directory: "Directorio de trabajo".into(),
+        working_dir_missing: "El directorio no existe".into(),
+        run: "Ejecutar".into(),
+        confirm_close_while_running: "¿Cerrar la ventana?".into(),
+        close_while_running: "El programa sigue en ejecución".into(),
+        kill_and_close: "Detener y cerrar".into(),
+        keep_running_and_close: "Seguir ejecutando y cerrar".into(),
+        cancel: "Cancelar".into(),
+        copy_as_json: "Copiar como JSON".into(),
+        man_page: "Página de manual".into(),
+        generate_man_page: "Guardar página de manual…".into(),
+        copy_man_page: "Copiar fuente roff".into(),
+        randomize: "🎲 Valores aleatorios".into(),
+        optional_bool_hint: "Haz clic para alternar entre sin definir (-), activado y desactivado"
+            .into(),
+        other_subcommand: "Otro".into(),
+        changes: "Cambios".into(),
+        no_changes: "Sin cambios".into(),
+        kill: "Detener".into(),
+        send_signal: "Enviar señal".into(),
+        running: "Ejecutando".into(),
+        ready: "Listo".into(),
+        finished: "Terminado".into(),
+        exit_code: "Código de salida".into(),
+        process_exited: "Proceso terminado (código {code})".into(),
+        expand_output: "Mostrar salida".into(),
+        save_output: "Guardar salida…".into(),
+        saved_to: "Guardado en {path}".into(),
+        pop_out_output: "Separar salida".into(),
+        load_earlier_output: "Cargar salida anterior".into(),
+        earlier_output: "Salida anterior".into(),
+        output_done: "Terminado ({lines} líneas)".into(),
+        more_output_than_expected: "Más salida de la esperada".into(),
+        line_numbers: "Números de línea".into(),
+        wrap_lines: "Ajustar líneas".into(),
+        detach: "Desacoplar".into(),
+        go_to_missing: "Ir al campo faltante".into(),
+        select_subcommand_required: "Selecciona un subcomando para continuar".into(),
+        output: "Salida".into(),
+        usage_stats: "ℹ Estadísticas de uso".into(),
+        notes: "Notas".into(),
+        view: "Ver".into(),
+        compact: "Compacto".into(),
+        theme: "Tema".into(),
+        theme_dark: "Oscuro".into(),
+        theme_light: "Claro".into(),
+        theme_high_contrast: "Alto contraste".into(),
+        about: "Acerca de".into(),
+        help_panel: "Ayuda".into(),
+        help_panel_empty: "Haz clic en un campo para ver su ayuda".into(),
+        version: "Versión".into(),
+        author: "Autor".into(),
+        ..Localization::default()
+    }
+}
+
+fn chinese() -> Localization {
+    Localization {
+        optional: "（可选）".into(),
+        value_empty: "（空）".into(),
+        value_default: "（默认）".into(),
+        select_file: "选择文件...".into(),
+        select_directory: "选择目录...".into(),
+        env_paste: "环境变量".into(),
+        field_history: "以前的值，按 Delete 删除悬停的项".into(),
+        new_value: "新值".into(),
+        reset: "重置".into(),
+        reset_to_default: "重置为默认值".into(),
+        error_is_required: ("参数“".into(), "”是必需的".into()),
+        disabled_by_conflict: ("已禁用，因为已设置 ".into(), "".into()),
+        error_too_few_values: ("参数“".into(), "”至少需要 ".into(), " 个值".into()),
+        error_too_many_values: ("参数“".into(), "”最多允许 ".into(), " 个值".into()),
+        error_invalid_email: "不是有效的电子邮件地址".into(),
+        warning_unresolved_template: "未解析的模板变量：".into(),
+        arguments: "参数".into(),
+        env_variables: "环境变量".into(),
+        error_env_var_cant_be_empty: "环境变量名不能为空".into(),
+        env_var_known: "由程序读取".into(),
+        env_var_unknown: "程序不读取".into(),
+        env_var_sets_arg: "也可以通过环境变量设置：".into(),
+        env_var_value_from: "值取自环境变量：".into(),
+        input: "输入".into(),
+        stdin_placeholder: "传递给标准输入的文本".into(),
+        text: "文本".into(),
+        file: "文件".into(),
+        stdin_size_warning: "输入超出限制（{current} / {max} 字节）".into(),
+        stdin_from_output: "使用上次输出".into(),
+        working_directory: "工作目录".into(),
+        working_dir_missing: "目录不存在".into(),
+        run: "运行".into(),
+        confirm_close_while_running: "关闭窗口？".into(),
+        close_while_running: "程序仍在运行".into(),
+        kill_and_close: "终止并关闭".into(),
+        keep_running_and_close: "保持运行并关闭".into(),
+        cancel: "取消".into(),
+        copy_as_json: "复制为 JSON".into(),
+        man_page: "手册页".into(),
+        generate_man_page: "保存手册页…".into(),
+        copy_man_page: "复制 roff 源码".into(),
+        randomize: "🎲 随机填充".into(),
+        optional_bool_hint: "点击在未设置 (-)、开和关之间切换".into(),
+        other_subcommand: "其他".into(),
+        changes: "更改".into(),
+        no_changes: "无更改".into(),
+        kill: "终止".into(),
+        send_signal: "发送信号".into(),
+        running: "运行中".into(),
+        ready: "就绪".into(),
+        finished: "已完成".into(),
+        exit_code: "退出码".into(),
+        process_exited: "进程已退出（代码 {code}）".into(),
+        expand_output: "展开输出".into(),
+        save_output: "保存输出…".into(),
+        saved_to: "已保存到 {path}".into(),
+        pop_out_output: "弹出输出".into(),
+        load_earlier_output: "加载更早的输出".into(),
+        earlier_output: "更早的输出".into(),
+        output_done: "完成（{lines} 行）".into(),
+        more_output_than_expected: "输出多于预期".into(),
+        line_numbers: "行号".into(),
+        wrap_lines: "自动换行".into(),
+        detach: "分离".into(),
+        go_to_missing: "转到缺少的字段".into(),
+        select_subcommand_required: "请选择一个子命令以继续".into(),
+        output: "输出".into(),
+        usage_stats: "ℹ 使用统计".into(),
+        notes: "备注".into(),
+        view: "视图".into(),
+        compact: "紧凑".into(),
+        theme: "主题".into(),
+        theme_dark: "深色".into(),
+        theme_light: "浅色".into(),
+        theme_high_contrast: "高对比度".into(),
+        about: "关于".into(),
+        help_panel: "帮助".into(),
+        help_panel_empty: "点击一个字段以查看其帮助".into(),
+        version: "版本".into(),
+        author: "作者".into(),
+        ..Localization::default()
+    }
+}
+
+fn japanese() -> Localization {
+    Localization {
+        optional: "（任意）".into(),
+        value_empty: "（空）".into(),
+        value_default: "（デフォルト）".into(),
+        select_file: "ファイルを選択...".into(),
+        select_directory: "ディレクトリを選択...".into(),
+        env_paste: "環境変数".into(),
+        field_history: "以前の値。Delete でカーソル下の値を削除".into(),
+        new_value: "新しい値".into(),
+        reset: "リセット".into(),
+        reset_to_default: "デフォルトに戻す".into(),
+        error_is_required: ("引数「".into(), "」は必須です".into()),
+        disabled_by_conflict: ("".into(), " が設定されているため無効です".into()),
+        error_too_few_values: (
+            "引数「".into(),
+            "」には少なくとも ".into(),
+            " 個の値が必要です".into(),
+        ),
+        error_too_many_values: (
+            "引数「".into(),
+            "」に指定できる値は最大 ".into(),
+            " 個です".into(),
+        ),
+        error_invalid_email: "有効なメールアドレスではありません".into(),
+        warning_unresolved_template: "未解決のテンプレート変数: ".into(),
+        arguments: "引数".into(),
+        env_variables: "環境変数".into(),
+        error_env_var_cant_be_empty: "環境変数名を空にすることはできません".into(),
+        env_var_known: "プログラムが読み取ります".into(),
+        env_var_unknown: "プログラムは読み取りません".into(),
+        env_var_sets_arg: "環境変数でも設定できます: ".into(),
+        env_var_value_from: "環境変数から取得した値: ".into(),
+        input: "入力".into(),
+        stdin_placeholder: "標準入力に渡すテキスト".into(),
+        text: "テキスト".into(),
+        file: "ファイル".into(),
+        stdin_size_warning: "入力が上限を超えています ({current} / {max} バイト)".into(),
+        stdin_from_output: "前回の出力を使う".into(),
+        working_directory: "作業ディレクトリ".into(),
+        working_dir_missing: "ディレクトリが存在しません".into(),
+        run: "実行".into(),
+        confirm_close_while_running: "ウィンドウを閉じますか？".into(),
+        close_while_running: "プログラムはまだ実行中です".into(),
+        kill_and_close: "強制終了して閉じる".into(),
+        keep_running_and_close: "実行を続けて閉じる".into(),
+        cancel: "キャンセル".into(),
+        copy_as_json: "JSON としてコピー".into(),
+        man_page: "man ページ".into(),
+        generate_man_page: "man ページを保存…".into(),
+        copy_man_page: "roff ソースをコピー".into(),
+        randomize: "🎲 ランダム入力".into(),
+        optional_bool_hint: "クリックで未設定 (-)、オン、オフを切り替え".into(),
+        other_subcommand: "その他".into(),
+        changes: "変更".into(),
+        no_changes: "変更なし".into(),
+        kill: "強制終了".into(),
+        send_signal: "シグナルを送信".into(),
+        running: "実行中".into(),
+        ready: "準備完了".into(),
+        finished: "完了".into(),
+        exit_code: "終了コード".into(),
+        process_exited: "プロセスが終了しました (コード {code})".into(),
+        expand_output: "出力を表示".into(),
+        save_output: "出力を保存…".into(),
+        saved_to: "{path} に保存しました".into(),
+        pop_out_output: "出力を別ウィンドウで表示".into(),
+        load_earlier_output: "以前の出力を読み込む".into(),
+        earlier_output: "以前の出力".into(),
+        output_done: "完了（{lines} 行）".into(),
+        more_output_than_expected: "予想より多くの出力があります".into(),
+        line_numbers: "行番号".into(),
+        wrap_lines: "行を折り返す".into(),
+        detach: "切り離す".into(),
+        go_to_missing: "未入力の項目へ移動".into(),
+        select_subcommand_required: "続けるにはサブコマンドを選択してください".into(),
+        output: "出力".into(),
+        usage_stats: "ℹ 使用統計".into(),
+        notes: "メモ".into(),
+        view: "表示".into(),
+        compact: "コンパクト".into(),
+        theme: "テーマ".into(),
+        theme_dark: "ダーク".into(),
+        theme_light: "ライト".into(),
+        theme_high_contrast: "ハイコントラスト".into(),
+        about: "情報".into(),
+        help_panel: "ヘルプ".into(),
+        help_panel_empty: "フィールドをクリックするとヘルプが表示されます".into(),
+        version: "バージョン".into(),
+        author: "作者".into(),
+        ..Localization::default()
+    }
+}
+
+fn arabic() -> Localization {
+    Localization {
+        optional: "(اختياري)".into(),
+        value_empty: "(فارغ)".into(),
+        value_default: "(افتراضي)".into(),
+        select_file: "اختر ملفًا...".into(),
+        select_directory: "اختر مجلدًا...".into(),
+        env_paste: "البيئة".into(),
+        field_history: "القيم السابقة، يزيل Delete القيمة المحددة".into(),
+        new_value: "قيمة جديدة".into(),
+        reset: "إعادة تعيين".into(),
+        reset_to_default: "إعادة التعيين إلى الافتراضي".into(),
+        error_is_required: ("الوسيط '".into(), "' مطلوب".into()),
+        disabled_by_conflict: ("معطل لأن ".into(), " معيّن".into()),
+        error_too_few_values: ("الوسيط '".into(), "' يتطلب على الأقل ".into(), " قيم".into()),
+        error_too_many_values: ("الوسيط '".into(), "' يسمح بحد أقصى ".into(), " قيم".into()),
+        error_invalid_email: "ليس عنوان بريد إلكتروني صالحًا".into(),
+        warning_unresolved_template: "متغيرات قالب غير محلولة: ".into(),
+        arguments: "الوسائط".into(),
+        env_variables: "متغيرات البيئة".into(),
+        error_env_var_cant_be_empty: "لا يمكن أن يكون اسم متغير البيئة فارغًا".into(),
+        env_var_known: "يقرأه البرنامج".into(),
+        env_var_unknown: "لا يقرأه البرنامج".into(),
+        env_var_sets_arg: "يمكن تعيينه أيضًا بمتغير البيئة ".into(),
+        env_var_value_from: "القيمة مأخوذة من متغير البيئة ".into(),
+        input: "الإدخال".into(),
+        stdin_placeholder: "نص يُمرَّر إلى الإدخال القياسي".into(),
+        text: "نص".into(),
+        file: "ملف".into(),
+        stdin_size_warning: "الإدخال يتجاوز الحد ({current} / {max} بايت)".into(),
+        stdin_from_output: "استخدام آخر مخرجات".into(),
+        working_directory: "مجلد العمل".into(),
+        working_dir_missing: "المجلد غير موجود".into(),
+        run: "تشغيل".into(),
+        confirm_close_while_running: "إغلاق النافذة؟".into(),
+        close_while_running: "البرنامج لا يزال قيد التشغيل".into(),
+        kill_and_close: "إيقاف وإغلاق".into(),
+        keep_running_and_close: "متابعة التشغيل والإغلاق".into(),
+        cancel: "إلغاء".into(),
+        copy_as_json: "نسخ بصيغة JSON".into(),
+        man_page: "صفحة الدليل".into(),
+        generate_man_page: "حفظ صفحة الدليل…".into(),
+        copy_man_page: "نسخ مصدر roff".into(),
+        randomize: "🎲 قيم عشوائية".into(),
+        optional_bool_hint: "انقر للتبديل بين غير محدد (-) وتشغيل وإيقاف".into(),
+        other_subcommand: "أخرى".into(),
+        changes: "التغييرات".into(),
+        no_changes: "لا توجد تغييرات".into(),
+        kill: "إيقاف".into(),
+        send_signal: "إرسال إشارة".into(),
+        running: "قيد التشغيل".into(),
+        ready: "جاهز".into(),
+        finished: "انتهى".into(),
+        exit_code: "رمز الخروج".into(),
+        process_exited: "انتهت العملية (الرمز {code})".into(),
+        expand_output: "عرض المخرجات".into(),
+        save_output: "حفظ المخرجات…".into(),
+        saved_to: "تم الحفظ في {path}".into(),
+        pop_out_output: "فصل المخرجات".into(),
+        load_earlier_output: "تحميل المخرجات السابقة".into(),
+        earlier_output: "المخرجات السابقة".into(),
+        output_done: "تم ({lines} سطر)".into(),
+        more_output_than_expected: "مخرجات أكثر من المتوقع".into(),
+        line_numbers: "أرقام الأسطر".into(),
+        wrap_lines: "التفاف الأسطر".into(),
+        detach: "فصل العملية".into(),
+        go_to_missing: "الانتقال إلى الحقل الناقص".into(),
+        select_subcommand_required: "اختر أمرًا فرعيًا للمتابعة".into(),
+        output: "المخرجات".into(),
+        usage_stats: "ℹ إحصائيات الاستخدام".into(),
+        notes: "ملاحظات".into(),
+        view: "عرض".into(),
+        compact: "مضغوط".into(),
+        theme: "السمة".into(),
+        theme_dark: "داكن".into(),
+        theme_light: "فاتح".into(),
+        theme_high_contrast: "تباين عالٍ".into(),
+        about: "حول".into(),
+        help_panel: "مساعدة".into(),
+        help_panel_empty: "انقر على حقل لعرض مساعدته".into(),
+        version: "الإصدار".into(),
+        author: "المؤلف".into(),
+        ..Localization::default()
+    }
+}
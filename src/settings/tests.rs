@@ -0,0 +1,154 @@
+use super::{compact_style, Localization, Settings};
+use clap::Command;
+use eframe::egui::WindowLevel;
+
+#[test]
+fn localizations_are_complete() {
+    assert_eq!(Localization::default().validate(), Ok(()));
+    for locale in Localization::supported_locales() {
+        assert_eq!(Localization::default_for_locale(locale).validate(), Ok(()));
+    }
+}
+
+#[test]
+fn child_env_var() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.child_env_var(), "KLASK_CHILD_APP");
+    settings.child_env_var = Some("KLASK_TEST_CHILD_ENV_VAR".into());
+    assert_eq!(settings.child_env_var(), "KLASK_TEST_CHILD_ENV_VAR");
+
+    assert!(!settings.is_child_process());
+    std::env::set_var("KLASK_TEST_CHILD_ENV_VAR", "0");
+    assert!(settings.is_child_process());
+    std::env::remove_var("KLASK_TEST_CHILD_ENV_VAR");
+}
+
+#[test]
+fn child_command() {
+    let mut settings = Settings::default();
+    settings.run_sequence.push(|| Command::new("second"));
+    let app = || Command::new("first");
+
+    let (step, command) = settings.child_command(app(), "0");
+    assert_eq!((step, command.get_name()), (0, "first"));
+
+    let (step, command) = settings.child_command(app(), "1");
+    assert_eq!((step, command.get_name()), (1, "second"));
+
+    // Unknown steps fall back to the main command
+    let (step, command) = settings.child_command(app(), "5");
+    assert_eq!((step, command.get_name()), (5, "first"));
+
+    let (step, command) = settings.child_command(app(), "invalid");
+    assert_eq!((step, command.get_name()), (0, "first"));
+}
+
+#[test]
+fn window_always_on_top() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.native_options().viewport.window_level, None);
+    settings.window_always_on_top = true;
+    assert_eq!(
+        settings.native_options().viewport.window_level,
+        Some(WindowLevel::AlwaysOnTop)
+    );
+}
+
+#[test]
+fn compact_mode() {
+    let style = Settings::default().style;
+    let compact = compact_style(style.clone());
+    assert_eq!(compact.spacing.item_spacing, eframe::egui::vec2(2.0, 2.0));
+    assert_eq!(compact.spacing.interact_size.y, 16.0);
+    assert_eq!(
+        compact.spacing.text_edit_width,
+        style.spacing.text_edit_width
+    );
+    assert_eq!(compact.visuals, style.visuals);
+}
+
+#[cfg(feature = "config-file")]
+#[test]
+fn config_file() {
+    use super::{ArgSort, Theme};
+    use eframe::egui::Color32;
+    use std::io::ErrorKind;
+
+    let path = std::env::temp_dir().join(format!("klask-{}.klask.toml", uuid::Uuid::new_v4()));
+    let load = |text: &str| {
+        std::fs::write(&path, text).unwrap();
+        Settings::from_config_file(&path)
+    };
+
+    let settings = load(
+        r##"
+        enable_env = "Variables"
+        arg_sort = "required_first"
+        theme = "high_contrast"
+        output_color_map = { ERROR = "#ff0000" }
+        conditional_visibility = [["format", "json", ["indent"]]]
+        run_button_ready_color = "#00ff00"
+        log_file = "logs/run.log"
+
+        [localization]
+        run = "Start"
+        error_is_required = ["Need ", "!"]
+        "##,
+    )
+    .unwrap();
+    assert_eq!(settings.enable_env.as_deref(), Some("Variables"));
+    assert_eq!(settings.arg_sort, ArgSort::RequiredFirst);
+    assert_eq!(settings.theme, Some(Theme::HighContrast));
+    assert_eq!(settings.output_color_map["ERROR"], Color32::RED);
+    assert_eq!(
+        settings.conditional_visibility,
+        vec![("format".into(), "json".into(), vec!["indent".into()])]
+    );
+    assert_eq!(settings.run_button_ready_color, Some(Color32::GREEN));
+    // Relative to the settings file, not to the working directory
+    assert_eq!(
+        settings.log_file,
+        Some(path.parent().unwrap().join("logs/run.log"))
+    );
+    assert_eq!(settings.localization.run, "Start");
+    assert_eq!(
+        settings.localization.error_is_required,
+        ("Need ".into(), "!".into())
+    );
+    assert_eq!(
+        settings.localization.kill,
+        Settings::default().localization.kill
+    );
+
+    for text in [
+        "unknown = true",
+        "arg_sort = \"random\"",
+        "compact_mode = 1",
+        "[localization]\nnope = \"\"",
+        "not toml",
+    ] {
+        assert_eq!(load(text).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    // Keys before the bad one aren't applied either
+    std::fs::write(&path, "arg_sort = \"required_first\"\ncompact_mode = 1").unwrap();
+    let mut settings = Settings::default();
+    assert!(settings.merge_config_file(&path).is_err());
+    assert_eq!(settings.arg_sort, Settings::default().arg_sort);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn max_concurrent_runs() {
+    let mut settings = Settings {
+        max_concurrent_runs: 0,
+        ..Default::default()
+    };
+    assert_eq!(settings.max_concurrent_runs(false), 1);
+
+    settings.max_concurrent_runs = 3;
+    assert_eq!(settings.max_concurrent_runs(false), 3);
+    // The runs would redirect each other's output
+    assert_eq!(settings.max_concurrent_runs(true), 1);
+}
@@ -0,0 +1,66 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+use toml::{Table, Value};
+
+/// Number of recorded runs after which the usage stats button is shown.
+pub const MIN_RUNS: u32 = 5;
+
+/// How often each argument was changed from its default, stored as a small toml file:
+/// ```toml
+/// runs = 7
+///
+/// [args]
+/// verbose = 3
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageStats {
+    pub runs: u32,
+    pub args: BTreeMap<String, u32>,
+}
+
+impl UsageStats {
+    /// A missing or unreadable file is treated as empty stats.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .map(|s| Self::parse(&s))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let args = self
+            .args
+            .iter()
+            .map(|(id, &count)| (id.clone(), Value::Integer(count.into())))
+            .collect();
+        let mut table = Table::new();
+        table.insert("runs".into(), Value::Integer(self.runs.into()));
+        table.insert("args".into(), Value::Table(args));
+        fs::write(path, table.to_string())
+    }
+
+    pub fn record(&mut self, changed: &[String]) {
+        self.runs += 1;
+        for id in changed {
+            *self.args.entry(id.clone()).or_default() += 1;
+        }
+    }
+
+    /// Entries that aren't counts are skipped.
+    fn parse(s: &str) -> Self {
+        let table: Table = s.parse().unwrap_or_default();
+        let count = |value: &Value| value.as_integer().and_then(|i| u32::try_from(i).ok());
+
+        Self {
+            runs: table.get("runs").and_then(count).unwrap_or_default(),
+            args: table
+                .get("args")
+                .and_then(Value::as_table)
+                .into_iter()
+                .flatten()
+                .filter_map(|(id, value)| Some((id.clone(), count(value)?)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
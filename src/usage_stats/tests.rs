@@ -0,0 +1,33 @@
+use super::UsageStats;
+
+#[test]
+fn round_trip() {
+    let path = std::env::temp_dir().join(format!("klask-stats-{}", uuid::Uuid::new_v4()));
+    let mut stats = UsageStats::default();
+    stats.record(&["verbose".into(), "say \"hi\"".into()]);
+    stats.record(&["verbose".into(), r"C:\path".into(), "".into()]);
+
+    stats.save(&path).unwrap();
+    let loaded = UsageStats::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, stats);
+    assert_eq!(loaded.runs, 2);
+    assert_eq!(loaded.args["verbose"], 2);
+    assert_eq!(loaded.args["say \"hi\""], 1);
+    assert_eq!(loaded.args[r"C:\path"], 1);
+}
+
+#[test]
+fn parse() {
+    let stats = UsageStats::parse("runs = 3\n\n[args]\nverbose = 2\n'a\\b' = 1\nname = \"x\"\n");
+    assert_eq!(stats.runs, 3);
+    assert_eq!(stats.args.len(), 2);
+    assert_eq!(stats.args[r"a\b"], 1);
+
+    assert_eq!(UsageStats::parse("not toml"), UsageStats::default());
+    assert_eq!(
+        UsageStats::load(std::path::Path::new("/nonexistent/klask-stats")),
+        UsageStats::default()
+    );
+}